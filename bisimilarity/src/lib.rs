@@ -1,19 +1,18 @@
-use std::rc::Rc;
-
 use mucalc::{Lts, StateId};
 use solver::index::{AsIndex, IndexedVec};
-use solver::symbolic::compose::FunsFormulas;
+use solver::symbolic::compose::{FormulaGenerator, FunsFormulas};
 use solver::symbolic::eq::{Expr, FixEq, FixType, FunId, VarId};
 use solver::symbolic::formula::{BasisElemId, Formula};
+use solver::sync::Shared;
 
 pub fn bisimilarity_to_fix(
-    lts1: Rc<Lts>,
-    lts2: Rc<Lts>,
+    lts1: Shared<Lts>,
+    lts2: Shared<Lts>,
 ) -> (IndexedVec<VarId, FixEq>, FunsFormulas) {
     let eq = FixEq { fix_type: FixType::Max, expr: Expr::Fun(FunId(0), vec![Expr::Var(VarId(0))]) };
     let eqs = IndexedVec::from(vec![eq]);
 
-    let generator: Rc<dyn Fn(BasisElemId) -> Formula> = Rc::new(move |b| {
+    let generator: FormulaGenerator = Shared::new(move |b| {
         let s1 = StateId(b.to_usize() / lts2.transitions.len());
         let s2 = StateId(b.to_usize() % lts2.transitions.len());
         formula_for(s1, s2, &lts1, &lts2)
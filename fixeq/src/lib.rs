@@ -0,0 +1,46 @@
+mod parser;
+mod print;
+
+#[cfg(test)]
+mod test;
+
+pub use parser::parse_fix_system;
+use solver::index::IndexedVec;
+use solver::symbolic::compose::{EqsFormulas, FunsFormulas};
+use solver::symbolic::eq::{FixEq, FunId, VarId};
+use solver::symbolic::formula::{BasisElemId, Formula};
+use solver::sync::Shared;
+
+/// A system of fixpoint equations, with the names used for its variables and functions.
+#[derive(Clone)]
+pub struct FixSystem {
+    pub vars: IndexedVec<VarId, String>,
+    pub eqs: IndexedVec<VarId, FixEq>,
+    pub funs: IndexedVec<FunId, Fun>,
+}
+
+/// A function given by its formula for each basis element, where the atoms refer to its
+/// arguments by their position.
+#[derive(Clone)]
+pub struct Fun {
+    pub name: String,
+    pub args: Vec<String>,
+    pub formulas: IndexedVec<BasisElemId, Formula>,
+}
+
+impl FixSystem {
+    /// The number of basis elements, which is the same for all functions. A system without
+    /// functions is solved over a single basis element.
+    pub fn basis_count(&self) -> usize {
+        self.funs.first().map_or(1, |fun| fun.formulas.len())
+    }
+
+    pub fn var_of(&self, name: &str) -> Option<VarId> {
+        self.vars.enumerate().find(|(_, var)| *var == name).map(|(i, _)| i)
+    }
+
+    pub fn to_formulas(&self) -> EqsFormulas {
+        let funs = self.funs.iter().map(|fun| fun.formulas.clone()).collect();
+        EqsFormulas::new(self.eqs.clone(), Shared::new(FunsFormulas::new(funs)))
+    }
+}
@@ -0,0 +1,64 @@
+use fixeq::parse_fix_system;
+use solver::global;
+use solver::local::solve;
+use solver::symbolic::analysis::Dependencies;
+use solver::symbolic::formula::BasisElemId;
+use solver::sync::Shared;
+
+fn main() {
+    let (flags, args) = std::env::args().skip(1).partition::<Vec<_>, _>(|a| a.starts_with("--"));
+    let global = flags.iter().any(|f| f == "--global");
+    let print_stats = flags.iter().any(|f| f == "--stats");
+    let print_system = flags.iter().any(|f| f == "--print");
+
+    let mut args = args.into_iter();
+    let path = args.next().expect("No equations file provided");
+    let var = args.next();
+    let b = args.next().map(|b| b.parse().expect("Failed to parse basis element")).unwrap_or(0);
+
+    let now = std::time::Instant::now();
+
+    let file = std::fs::read_to_string(path).expect("Failed to read equations file");
+    let system = parse_fix_system(&file).expect("Failed to parse equations file");
+
+    if print_system {
+        print!("{system}");
+    }
+
+    // Without a variable solve the outermost one.
+    let init_v = match var {
+        Some(var) => system.var_of(&var).unwrap_or_else(|| panic!("Unknown variable {var}")),
+        None => system.eqs.last_index().expect("No equations provided"),
+    };
+    let init_b = BasisElemId(b);
+    assert!(b < system.basis_count(), "Basis element {b} out of range");
+    let formulas = Shared::new(system.to_formulas());
+
+    println!("Preprocessing took {:?}", now.elapsed());
+    if print_stats {
+        println!("Alternation depth: {}", Dependencies::new(&system.eqs).alternation_depth());
+    }
+
+    let now = std::time::Instant::now();
+
+    if global {
+        let solution = global::solve(system.basis_count(), formulas);
+
+        println!("Solve took {:?}", now.elapsed());
+        for (var, solution) in solution.enumerate() {
+            let elems = solution.iter().map(|b| b.0.to_string()).collect::<Vec<_>>();
+            println!("{}: {}", system.vars[var], elems.join(", "));
+        }
+        return;
+    }
+
+    let result = solve(init_b, init_v, formulas);
+    let is_winning = result.is_winning();
+
+    println!("Solve took {:?}", now.elapsed());
+    if print_stats {
+        println!("{}", result.stats);
+    }
+    let var = &system.vars[init_v];
+    println!("{var} {} satisfied by basis element {b}", if is_winning { "is" } else { "is not" });
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use chumsky::error::Simple;
+use chumsky::primitive::{choice, end, just};
+use chumsky::recursive::{recursive, Recursive};
+use chumsky::text::{self, keyword, TextParser};
+use chumsky::{BoxedParser, Parser};
+use solver::index::IndexedVec;
+use solver::symbolic::eq::{Expr, FixEq, FixType, FunId, VarId};
+use solver::symbolic::formula::{BasisElemId, Formula};
+
+use crate::{FixSystem, Fun};
+
+type Span = Range<usize>;
+type Name = (String, Span);
+
+const KEYWORDS: [&str; 5] = ["mu", "nu", "fun", "true", "false"];
+
+/// An expression or formula before its names are resolved.
+enum Ast<L> {
+    Leaf(L),
+    And(Vec<Ast<L>>),
+    Or(Vec<Ast<L>>),
+}
+
+enum ExprLeaf {
+    Var(Name),
+    Fun(Name, Vec<Ast<ExprLeaf>>),
+}
+
+// An argument at a basis element.
+type FormulaLeaf = (Name, usize);
+
+enum Item {
+    Eq(FixType, Name, Ast<ExprLeaf>),
+    Fun(Name, Vec<Name>, Vec<(usize, Span, Ast<FormulaLeaf>)>),
+}
+
+/// Parses a system of fixpoint equations like:
+///
+/// ```text
+/// mu X = Y || f(X, Y)
+/// nu Y = X && true
+///
+/// fun f(x, y) {
+///     0: x@0 && y@1
+///     1: false
+/// }
+/// ```
+///
+/// Each variable is defined by an equation, in order from the innermost to the outermost.
+/// Functions are given by their formula for each basis element, in order, where `x@b` is
+/// the argument `x` at the basis element `b`. All functions must have the same number of
+/// basis elements.
+pub fn parse_fix_system(source: &str) -> Result<FixSystem, Vec<Simple<char>>> {
+    let ident =
+        text::ident().try_map(|name: String, span: Span| match KEYWORDS.contains(&&*name) {
+            true => Err(Simple::custom(span, format!("Unexpected keyword '{name}'"))),
+            false => Ok((name, span)),
+        });
+    let ident = ident.padded();
+    let number = text::int(10).try_map(|n: String, span: Span| {
+        n.parse::<usize>().map_err(|_| Simple::custom(span, format!("Number {n} is too large")))
+    });
+    let number = number.padded();
+    let comma = just(',').padded();
+
+    let expr = boolean(|expr| {
+        let args = expr.separated_by(comma).delimited_by(just('('), just(')'));
+        let leaf = ident.then(args.padded().or_not()).map(|(name, args)| match args {
+            Some(args) => ExprLeaf::Fun(name, args),
+            None => ExprLeaf::Var(name),
+        });
+        leaf.boxed()
+    });
+    let formula = boolean(|_| ident.then_ignore(just('@').padded()).then(number).boxed());
+
+    let fix_type = choice((keyword("mu").to(FixType::Min), keyword("nu").to(FixType::Max)));
+    let eq = fix_type.padded().then(ident).then_ignore(just('=').padded()).then(expr);
+    let eq = eq.map(|((fix_type, name), expr)| Item::Eq(fix_type, name, expr));
+
+    let row = number.map_with_span(|b, span| (b, span)).then_ignore(just(':').padded());
+    let row = row.then(formula).map(|((b, span), formula)| (b, span, formula));
+    let rows = row.repeated().delimited_by(just('{').padded(), just('}').padded());
+    let args = ident.separated_by(comma).delimited_by(just('('), just(')')).padded();
+    let fun = keyword("fun").padded().ignore_then(ident).then(args).then(rows);
+    let fun = fun.map(|((name, args), rows)| Item::Fun(name, args, rows));
+
+    let items = choice((eq, fun)).repeated().padded().then_ignore(end());
+
+    let items = items.parse(source)?;
+    Resolver::default().resolve(items)
+}
+
+/// Parses conjunctions and disjunctions of the leaves, with `&&` binding tighter than `||`.
+fn boolean<'a, L: 'a>(
+    leaf: impl FnOnce(
+        Recursive<'a, char, Ast<L>, Simple<char>>,
+    ) -> BoxedParser<'a, char, L, Simple<char>>,
+) -> Recursive<'a, char, Ast<L>, Simple<char>> {
+    recursive(|ast| {
+        let tt = keyword("true").map(|_| Ast::And(Vec::new()));
+        let ff = keyword("false").map(|_| Ast::Or(Vec::new()));
+        let group = ast.clone().delimited_by(just('('), just(')'));
+        let leaf = leaf(ast).map(Ast::Leaf);
+        let atom = choice((tt, ff, group, leaf)).padded().boxed();
+
+        let and = atom.separated_by(just("&&").padded()).at_least(1).map(unwrap_one_or(Ast::And));
+        and.boxed().separated_by(just("||").padded()).at_least(1).map(unwrap_one_or(Ast::Or))
+    })
+}
+
+fn unwrap_one_or<T>(f: impl Fn(Vec<T>) -> T + Clone) -> impl Fn(Vec<T>) -> T + Clone {
+    move |mut v| match v.len() {
+        1 => v.pop().unwrap(),
+        _ => f(v),
+    }
+}
+
+/// Resolves the names in the parsed items, collecting all the errors found.
+#[derive(Default)]
+struct Resolver {
+    vars: HashMap<String, VarId>,
+    funs: HashMap<String, (FunId, usize)>,
+    errors: Vec<Simple<char>>,
+}
+
+impl Resolver {
+    fn resolve(mut self, items: Vec<Item>) -> Result<FixSystem, Vec<Simple<char>>> {
+        let mut system =
+            FixSystem { vars: IndexedVec::new(), eqs: IndexedVec::new(), funs: IndexedVec::new() };
+
+        // Declare all the names first, since they can be used before their definition.
+        let mut eqs = Vec::new();
+        let mut funs = Vec::new();
+        for item in items {
+            match item {
+                Item::Eq(fix_type, (name, span), expr) => {
+                    let var = system.vars.push(name.clone());
+                    if self.vars.insert(name.clone(), var).is_some() {
+                        self.error(span, format!("Variable '{name}' is defined more than once"));
+                    }
+                    eqs.push((fix_type, expr));
+                }
+                Item::Fun((name, span), args, rows) => {
+                    let fun = FunId(funs.len());
+                    if self.funs.insert(name.clone(), (fun, args.len())).is_some() {
+                        self.error(
+                            span.clone(),
+                            format!("Function '{name}' is defined more than once"),
+                        );
+                    }
+                    for (i, (arg, span)) in args.iter().enumerate() {
+                        if args[..i].iter().any(|(prev, _)| prev == arg) {
+                            self.error(
+                                span.clone(),
+                                format!("Argument '{arg}' of '{name}' is declared more than once"),
+                            );
+                        }
+                    }
+                    funs.push(((name, span), args, rows));
+                }
+            }
+        }
+
+        for (fix_type, expr) in eqs {
+            let expr = self.resolve_expr(expr);
+            system.eqs.push(FixEq { fix_type, expr });
+        }
+
+        let basis_count = funs.first().map_or(1, |(_, _, rows)| rows.len());
+        for ((name, span), args, rows) in funs {
+            if rows.len() != basis_count {
+                let msg = format!(
+                    "Function '{name}' has {} basis elements, expected {basis_count}",
+                    rows.len()
+                );
+                self.error(span, msg);
+            }
+            let formulas = rows
+                .into_iter()
+                .enumerate()
+                .map(|(i, (b, span, formula))| {
+                    if b != i {
+                        self.error(span, format!("Expected basis element {i}, found {b}"));
+                    }
+                    self.resolve_formula(formula, &args, basis_count)
+                })
+                .collect();
+            let args = args.into_iter().map(|(arg, _)| arg).collect();
+            system.funs.push(Fun { name, args, formulas });
+        }
+
+        match self.errors.is_empty() {
+            true => Ok(system),
+            false => Err(self.errors),
+        }
+    }
+
+    fn resolve_expr(&mut self, ast: Ast<ExprLeaf>) -> Expr {
+        match ast {
+            Ast::Leaf(ExprLeaf::Var((name, span))) => match self.vars.get(&name) {
+                Some(&var) => Expr::Var(var),
+                None => {
+                    self.error(span, format!("Undefined variable '{name}'"));
+                    Expr::BOT
+                }
+            },
+            Ast::Leaf(ExprLeaf::Fun((name, span), args)) => {
+                let args = args.into_iter().map(|arg| self.resolve_expr(arg)).collect::<Vec<_>>();
+                match self.funs.get(&name) {
+                    Some(&(fun, arity)) if arity == args.len() => Expr::Fun(fun, args),
+                    Some(&(_, arity)) => {
+                        let msg = format!(
+                            "Function '{name}' takes {arity} arguments, found {}",
+                            args.len()
+                        );
+                        self.error(span, msg);
+                        Expr::BOT
+                    }
+                    None => {
+                        self.error(span, format!("Undefined function '{name}'"));
+                        Expr::BOT
+                    }
+                }
+            }
+            Ast::And(children) => {
+                Expr::And(children.into_iter().map(|c| self.resolve_expr(c)).collect())
+            }
+            Ast::Or(children) => {
+                Expr::Or(children.into_iter().map(|c| self.resolve_expr(c)).collect())
+            }
+        }
+    }
+
+    fn resolve_formula(
+        &mut self,
+        ast: Ast<FormulaLeaf>,
+        args: &[Name],
+        basis_count: usize,
+    ) -> Formula {
+        match ast {
+            Ast::Leaf(((name, span), b)) => {
+                let Some(i) = args.iter().position(|(arg, _)| *arg == name) else {
+                    self.error(span, format!("Undefined argument '{name}'"));
+                    return Formula::FALSE;
+                };
+                if b >= basis_count {
+                    let msg =
+                        format!("Basis element {b} out of range, expected less than {basis_count}");
+                    self.error(span, msg);
+                }
+                Formula::Atom(BasisElemId(b), VarId(i))
+            }
+            Ast::And(children) => Formula::And(
+                children.into_iter().map(|c| self.resolve_formula(c, args, basis_count)).collect(),
+            ),
+            Ast::Or(children) => Formula::Or(
+                children.into_iter().map(|c| self.resolve_formula(c, args, basis_count)).collect(),
+            ),
+        }
+    }
+
+    fn error(&mut self, span: Span, msg: String) {
+        self.errors.push(Simple::custom(span, msg));
+    }
+}
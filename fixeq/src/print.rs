@@ -0,0 +1,138 @@
+use std::fmt;
+
+use solver::symbolic::eq::{Expr, FixType};
+use solver::symbolic::formula::Formula;
+
+use crate::FixSystem;
+
+/// Prints the system in the format read by [`crate::parse_fix_system`].
+impl fmt::Display for FixSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (var, eq) in self.eqs.enumerate() {
+            let fix_type = match eq.fix_type {
+                FixType::Min => "mu",
+                FixType::Max => "nu",
+            };
+            write!(f, "{fix_type} {} = ", self.vars[var])?;
+            self.write_expr(f, &eq.expr)?;
+            writeln!(f)?;
+        }
+
+        for fun in self.funs.iter() {
+            writeln!(f)?;
+            writeln!(f, "fun {}({}) {{", fun.name, fun.args.join(", "))?;
+            for (b, formula) in fun.formulas.enumerate() {
+                write!(f, "    {}: ", b.0)?;
+                write_formula(f, formula, &fun.args)?;
+                writeln!(f)?;
+            }
+            writeln!(f, "}}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FixSystem {
+    fn write_expr(&self, f: &mut fmt::Formatter<'_>, expr: &Expr) -> fmt::Result {
+        match expr {
+            Expr::Var(var) => write!(f, "{}", self.vars[*var]),
+            Expr::Fun(fun, args) => {
+                write!(f, "{}(", self.funs[*fun].name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    self.write_expr(f, arg)?;
+                }
+                write!(f, ")")
+            }
+            Expr::And(children) => write_connective(f, Op::And, children, |f, child| {
+                let op = op_of_expr(child);
+                write_child(f, Op::And, op, |f| self.write_expr(f, child))
+            }),
+            Expr::Or(children) => write_connective(f, Op::Or, children, |f, child| {
+                let op = op_of_expr(child);
+                write_child(f, Op::Or, op, |f| self.write_expr(f, child))
+            }),
+        }
+    }
+}
+
+fn write_formula(f: &mut fmt::Formatter<'_>, formula: &Formula, args: &[String]) -> fmt::Result {
+    match formula {
+        Formula::Atom(b, i) => write!(f, "{}@{}", args[i.0], b.0),
+        Formula::And(children) => write_connective(f, Op::And, children, |f, child| {
+            let op = op_of_formula(child);
+            write_child(f, Op::And, op, |f| write_formula(f, child, args))
+        }),
+        Formula::Or(children) => write_connective(f, Op::Or, children, |f, child| {
+            let op = op_of_formula(child);
+            write_child(f, Op::Or, op, |f| write_formula(f, child, args))
+        }),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Op {
+    And,
+    Or,
+}
+
+// The connective a child is printed with, if it's printed with one.
+fn op_of_expr(expr: &Expr) -> Option<Op> {
+    match expr {
+        Expr::And(children) if children.len() > 1 => Some(Op::And),
+        Expr::Or(children) if children.len() > 1 => Some(Op::Or),
+        _ => None,
+    }
+}
+
+fn op_of_formula(formula: &Formula) -> Option<Op> {
+    match formula {
+        Formula::And(children) if children.len() > 1 => Some(Op::And),
+        Formula::Or(children) if children.len() > 1 => Some(Op::Or),
+        _ => None,
+    }
+}
+
+fn write_connective<T>(
+    f: &mut fmt::Formatter<'_>,
+    op: Op,
+    children: &[T],
+    mut write_child: impl FnMut(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    let (sep, empty) = match op {
+        Op::And => (" && ", "true"),
+        Op::Or => (" || ", "false"),
+    };
+    if children.is_empty() {
+        return write!(f, "{empty}");
+    }
+    for (i, child) in children.iter().enumerate() {
+        if i != 0 {
+            write!(f, "{sep}")?;
+        }
+        write_child(f, child)?;
+    }
+    Ok(())
+}
+
+// Children are parenthesized when needed for precedence, and also when they use the same
+// connective as their parent, so that parsing the result gives back the same tree, except
+// for connectives with a single child which are printed as the child itself.
+fn write_child(
+    f: &mut fmt::Formatter<'_>,
+    parent: Op,
+    child: Option<Op>,
+    write: impl FnOnce(&mut fmt::Formatter<'_>) -> fmt::Result,
+) -> fmt::Result {
+    match child {
+        Some(child) if parent == Op::And || child == Op::Or => {
+            write!(f, "(")?;
+            write(f)?;
+            write!(f, ")")
+        }
+        _ => write(f),
+    }
+}
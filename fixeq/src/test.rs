@@ -0,0 +1,65 @@
+use solver::global;
+use solver::local::solve;
+use solver::symbolic::formula::BasisElemId;
+use solver::sync::Shared;
+
+use crate::parse_fix_system;
+
+const SYSTEM: &str = "\
+mu X = Y || f(X, Y)
+nu Y = X && (f(Y, Y) || g(X)) && true
+mu Z = (X || Y) && (Z || g(Z || Y))
+
+fun f(x, y) {
+    0: x@1 && (y@0 || y@1)
+    1: false
+}
+
+fun g(x) {
+    0: x@0 || x@1 && x@0
+    1: false
+}
+";
+
+#[test]
+fn print_parsed() {
+    let system = parse_fix_system(SYSTEM).unwrap();
+    assert_eq!(system.to_string(), SYSTEM);
+    assert_eq!(system.basis_count(), 2);
+    assert_eq!(system.var_of("Z"), system.eqs.last_index());
+}
+
+#[test]
+fn solve_parsed() {
+    let system = parse_fix_system(SYSTEM).unwrap();
+    let formulas = Shared::new(system.to_formulas());
+    let solution = global::solve(system.basis_count(), formulas.clone());
+
+    let expected = [("X", [true, false]), ("Y", [true, false]), ("Z", [true, false])];
+    for (name, winning) in expected {
+        let var = system.var_of(name).unwrap();
+        for (b, winning) in winning.into_iter().enumerate() {
+            let b = BasisElemId(b);
+            assert_eq!(solve(b, var, formulas.clone()).is_winning(), winning, "{name} {b:?}");
+            assert_eq!(solution[var].contains(&b), winning, "{name} {b:?}");
+        }
+    }
+}
+
+#[test]
+fn resolve_errors() {
+    let errors = |source: &str| parse_fix_system(source).err().unwrap().len();
+
+    assert_eq!(errors("mu X = Y"), 1);
+    assert_eq!(errors("mu X = X\nnu X = X"), 1);
+    assert_eq!(errors("mu X = f(X)"), 1);
+    assert_eq!(errors("mu X = f(X)\nfun f(x, y) { 0: x@0 }"), 1);
+    assert_eq!(errors("mu X = f(X)\nfun f(x) { 0: y@0 }"), 1);
+    assert_eq!(errors("mu X = f(X)\nfun f(x) { 0: x@1 }"), 1);
+    assert_eq!(errors("mu X = f(X)\nfun f(x) { 1: x@0 }"), 1);
+    assert_eq!(errors("fun f(x) { 0: true }\nfun g(x) { 0: true 1: true }"), 1);
+    assert_eq!(errors("mu X = f(X, X)\nfun f(x, x) { 0: x@0 }"), 1);
+    assert_eq!(errors("mu X = f(X)\nfun f(x) { 0: x@99999999999999999999 }"), 1);
+    assert!(parse_fix_system("mu true = X").is_err());
+    assert!(parse_fix_system("mu X = X &&").is_err());
+}
@@ -1,14 +1,17 @@
 use std::collections::HashSet;
-use std::rc::Rc;
 
 use solver::index::{AsIndex, IndexedSet, IndexedVec};
-use solver::symbolic::compose::FunsFormulas;
+use solver::symbolic::compose::{FormulaGenerator, FunsFormulas};
 use solver::symbolic::eq::{Expr, FixEq, FixType, FunId, VarId};
-use solver::symbolic::formula::{BasisElemId, Formula};
+use solver::symbolic::formula::Formula;
+use solver::sync::Shared;
 
 use crate::{Act, Lts, MuCalc, StateId, Var};
 
-pub fn mucalc_to_fix(mu_calc: &MuCalc, lts: Rc<Lts>) -> (IndexedVec<VarId, FixEq>, FunsFormulas) {
+pub fn mucalc_to_fix(
+    mu_calc: &MuCalc,
+    lts: Shared<Lts>,
+) -> (IndexedVec<VarId, FixEq>, FunsFormulas) {
     let mut ctx = ConvContext {
         lts: &lts,
         funcs: IndexedSet::default(),
@@ -42,7 +45,7 @@ enum FunKind {
 }
 
 struct ConvContext<'a> {
-    lts: &'a Rc<Lts>,
+    lts: &'a Shared<Lts>,
 
     // Maps each combination of box/diamond + act to a function id
     funcs: IndexedSet<FunId, (FunKind, &'a Act)>,
@@ -52,7 +55,7 @@ struct ConvContext<'a> {
     scope_vars: HashSet<VarId>,
 
     // Output uncomposed formulas
-    formulas: IndexedVec<FunId, Rc<dyn Fn(BasisElemId) -> Formula>>,
+    formulas: IndexedVec<FunId, FormulaGenerator>,
     // Output fixpoint equations
     sys: IndexedVec<VarId, FixEq>,
 }
@@ -99,8 +102,9 @@ impl<'a> ConvContext<'a> {
                 };
 
                 let lts = self.lts.clone();
-                self.formulas
-                    .push(Rc::new(move |b| make_formula(&lts.transitions[StateId(b.to_usize())])))
+                self.formulas.push(Shared::new(move |b| {
+                    make_formula(&lts.transitions[StateId(b.to_usize())])
+                }))
             }
         };
 
@@ -1,32 +1,59 @@
-use std::rc::Rc;
-
 use mucalc::{mucalc_to_fix, parse_aut, parse_mucalc};
+use solver::global;
 use solver::local::solve;
+use solver::symbolic::analysis::Dependencies;
 use solver::symbolic::compose::EqsFormulas;
+use solver::sync::Shared;
 
 fn main() {
-    let alt_path = std::env::args().nth(1).expect("No alt file provided");
-    let mucalc_path = std::env::args().nth(2).expect("No mucalc file provided");
+    let (flags, args) = std::env::args().skip(1).partition::<Vec<_>, _>(|a| a.starts_with("--"));
+    let global = flags.iter().any(|f| f == "--global");
+    let print_stats = flags.iter().any(|f| f == "--stats");
+    // Statistics are only collected by the local solver.
+    if global && print_stats {
+        panic!("Conflicting flags --global and --stats, only the local solver collects statistics");
+    }
+
+    let mut args = args.into_iter();
+    let alt_path = args.next().expect("No alt file provided");
+    let mucalc_path = args.next().expect("No mucalc file provided");
 
     let now = std::time::Instant::now();
 
     let alt_file = std::fs::read_to_string(alt_path).expect("Failed to read alt file");
     let mucalc_file = std::fs::read_to_string(mucalc_path).expect("Failed to read mucalc file");
 
-    let lts = Rc::new(parse_aut(&alt_file).expect("Failed to parse alt file"));
+    let lts = Shared::new(parse_aut(&alt_file).expect("Failed to parse alt file"));
     let mucalc = parse_mucalc(&mucalc_file).expect("Failed to parse mucalc file");
 
     let (eqs, funs_formulas) = mucalc_to_fix(&mucalc, lts.clone());
     let init_b = lts.first_state.to_basis_elem();
     let init_v = eqs.last_index().unwrap();
-    let formulas = Rc::new(EqsFormulas::new(eqs, Rc::new(funs_formulas)));
+    let alternation_depth = Dependencies::new(&eqs).alternation_depth();
+    let formulas = Shared::new(EqsFormulas::new(eqs, Shared::new(funs_formulas)));
 
     println!("Preprocessing took {:?}", now.elapsed());
+    if print_stats {
+        println!("Alternation depth: {alternation_depth}");
+    }
 
     let now = std::time::Instant::now();
 
-    let is_winning = solve(init_b, init_v, formulas);
+    if global {
+        let solution = global::solve(lts.transitions.len(), formulas);
+
+        println!("Solve took {:?}", now.elapsed());
+        let states = solution[init_v].iter().map(|b| b.0.to_string()).collect::<Vec<_>>();
+        println!("The formula is satisfied by the states: {}", states.join(", "));
+        return;
+    }
+
+    let result = solve(init_b, init_v, formulas);
+    let is_winning = result.is_winning();
 
     println!("Solve took {:?}", now.elapsed());
+    if print_stats {
+        println!("{}", result.stats);
+    }
     println!("The formula {} satisfied", if is_winning { "is" } else { "is not" });
 }
@@ -7,7 +7,7 @@ use chumsky::Parser;
 
 use crate::{Act, MuCalc, Var};
 
-pub fn parse_mucalc<'a>(source: &str) -> Result<MuCalc, Vec<Simple<char>>> {
+pub fn parse_mucalc(source: &str) -> Result<MuCalc, Vec<Simple<char>>> {
     let expr = recursive(|expr| {
         let var = text::ident().map(Var).padded();
 
@@ -35,9 +35,7 @@ pub fn parse_mucalc<'a>(source: &str) -> Result<MuCalc, Vec<Simple<char>>> {
         let eta = |eta, f| keyword(eta).padded().then(var).then(dot).map(move |((_, v), _)| (f, v));
         let mu = eta("mu", MuCalc::Mu as fn(_, _) -> _).boxed();
         let nu = eta("nu", MuCalc::Nu as fn(_, _) -> _).boxed();
-        let fix = choice((mu, nu)).repeated().then(or).foldr(|(f, v), e| f(v, Box::new(e)));
-
-        fix
+        choice((mu, nu)).repeated().then(or).foldr(|(f, v), e| f(v, Box::new(e)))
     });
 
     expr.then_ignore(end()).parse(source)
@@ -1,13 +1,12 @@
-use std::rc::Rc;
-
 use solver::local::solve;
 use solver::symbolic::compose::EqsFormulas;
+use solver::sync::Shared;
 
 use crate::{mucalc_to_fix, parse_aut, parse_mucalc};
 
 fn run_test(aut_path: &str, mucalc_path: &str, expected: bool) {
     let aut = std::fs::read_to_string(aut_path).unwrap();
-    let lts = Rc::new(parse_aut(&aut).unwrap());
+    let lts = Shared::new(parse_aut(&aut).unwrap());
 
     let mucalc = std::fs::read_to_string(mucalc_path).unwrap();
     let parse_mu_calc = parse_mucalc(&mucalc);
@@ -16,9 +15,9 @@ fn run_test(aut_path: &str, mucalc_path: &str, expected: bool) {
     let (eqs, funs_formulas) = mucalc_to_fix(&mucalc, lts.clone());
     let init_b = lts.first_state.to_basis_elem();
     let init_v = eqs.last_index().unwrap();
-    let formulas = Rc::new(EqsFormulas::new(eqs, Rc::new(funs_formulas)));
+    let formulas = Shared::new(EqsFormulas::new(eqs, Shared::new(funs_formulas)));
 
-    let is_valid = solve(init_b, init_v, formulas);
+    let is_valid = solve(init_b, init_v, formulas).is_winning();
 
     assert_eq!(is_valid, expected);
 }
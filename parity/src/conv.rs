@@ -24,7 +24,7 @@ pub fn parity_game_to_fix(
                 _ => FixType::Min,
             };
 
-            let children = n.successors.iter().map(|n| Expr::Var(node_id_to_var_id[&n])).collect();
+            let children = n.successors.iter().map(|n| Expr::Var(node_id_to_var_id[n])).collect();
             let expr = match n.player {
                 Player::P0 => Expr::Or(children),
                 Player::P1 => Expr::And(children),
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use solver::index::IndexedVec;
+use solver::strategy::{GetRelevance, NodeId, ParityGraph, ParitySolution, Player, Relevance};
+
+use crate::ParityGame;
+
+/// A parity game with its nodes numbered consecutively, to be solved directly with strategy
+/// iteration instead of being encoded as fixpoint equations.
+pub struct ExplicitGame {
+    ids: IndexedVec<NodeId, usize>,
+    relevance: IndexedVec<NodeId, usize>,
+    players: IndexedVec<NodeId, Player>,
+    successors: IndexedVec<NodeId, Vec<NodeId>>,
+    predecessors: IndexedVec<NodeId, Vec<NodeId>>,
+    nodes_by_reward: Vec<NodeId>,
+}
+
+impl ExplicitGame {
+    pub fn new(pg: &ParityGame) -> Self {
+        let ids = pg.nodes.iter().map(|n| n.id).collect::<IndexedVec<NodeId, _>>();
+        let node_of = ids.enumerate().map(|(n, &id)| (id, n)).collect::<HashMap<_, _>>();
+
+        let relevance = pg.nodes.iter().map(|n| n.relevance).collect::<IndexedVec<_, _>>();
+        let players = pg.nodes.iter().map(|n| n.player).collect::<IndexedVec<_, _>>();
+
+        let mut successors = IndexedVec::from(vec![Vec::new(); ids.len()]);
+        let mut predecessors = IndexedVec::from(vec![Vec::new(); ids.len()]);
+        for (n, node) in pg.nodes.iter().enumerate() {
+            for s in &node.successors {
+                let (n, s) = (NodeId(n), node_of[s]);
+                successors[n].push(s);
+                predecessors[s].push(n);
+            }
+        }
+
+        let mut nodes_by_reward = ids.indexes().collect::<Vec<_>>();
+        nodes_by_reward
+            .sort_unstable_by_key(|&node| Relevance { priority: relevance[node], node }.reward());
+
+        Self { ids, relevance, players, successors, predecessors, nodes_by_reward }
+    }
+
+    /// The id of the node in the original game.
+    pub fn id_of(&self, n: NodeId) -> usize {
+        self.ids[n]
+    }
+}
+
+impl GetRelevance for ExplicitGame {
+    fn relevance_of(&self, node: NodeId) -> Relevance {
+        Relevance { priority: self.relevance[node], node }
+    }
+}
+
+impl ParityGraph for ExplicitGame {
+    fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn player_of(&self, n: NodeId) -> Player {
+        self.players[n]
+    }
+
+    fn successors_of(&self, n: NodeId) -> impl Iterator<Item = NodeId> {
+        self.successors[n].iter().copied()
+    }
+
+    fn predecessors_of(&self, n: NodeId) -> impl Iterator<Item = NodeId> {
+        self.predecessors[n].iter().copied()
+    }
+
+    fn nodes_sorted_by_reward(&self) -> impl Iterator<Item = NodeId> {
+        self.nodes_by_reward.iter().copied()
+    }
+}
+
+/// Solves the game with the given explicit solver, like [`solver::strategy::solve_parity_game`] or
+/// those in [`solver::reference`], returning the winner of each node by its id.
+pub fn solve_explicit(
+    pg: &ParityGame,
+    solve: impl FnOnce(&ExplicitGame) -> ParitySolution,
+) -> HashMap<usize, Player> {
+    let game = ExplicitGame::new(pg);
+    let solution = solve(&game);
+    solution.winner.enumerate().map(|(n, &winner)| (game.id_of(n), winner)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use solver::strategy::{solve_parity_game, Player};
+
+    use super::solve_explicit;
+    use crate::parse_parity_game;
+
+    #[test]
+    fn sparse_ids() {
+        // Player 1 escapes from the cycle through 0 and 5, won by player 0, to the loop on 7.
+        let game =
+            parse_parity_game("parity 7;\n0 1 0 5;\n5 2 1 0,7;\n7 3 0 7;\n9 4 1 9;\n").unwrap();
+        let winners = solve_explicit(&game, solve_parity_game);
+
+        assert_eq!(winners.len(), 4);
+        for (id, winner) in [(0, Player::P1), (5, Player::P1), (7, Player::P1), (9, Player::P0)] {
+            assert_eq!(winners[&id], winner, "Node {id}");
+        }
+    }
+}
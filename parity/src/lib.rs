@@ -1,10 +1,12 @@
 mod conv;
+mod explicit;
 mod parser;
 
 #[cfg(test)]
 mod test;
 
 pub use conv::parity_game_to_fix;
+pub use explicit::{solve_explicit, ExplicitGame};
 pub use parser::parse_parity_game;
 use solver::strategy::Player;
 
@@ -1,24 +1,74 @@
-use std::rc::Rc;
-
-use parity::{parity_game_to_fix, parse_parity_game};
-use solver::local::solve;
+use parity::{parity_game_to_fix, parse_parity_game, solve_explicit, ExplicitGame};
+use solver::global;
+use solver::local::LocalSolver;
+use solver::reference::{priority_promotion, zielonka};
+use solver::strategy::{solve_parity_game, Player, SwitchRule};
 use solver::symbolic::compose::EqsFormulas;
 use solver::symbolic::formula::BasisElemId;
+use solver::sync::Shared;
 
 fn main() {
-    let path = std::env::args().nth(1).expect("No parity game file provided");
-    let node = std::env::args()
-        .nth(2)
-        .map(|n| n.parse().expect("Failed to parse starting node"))
-        .unwrap_or(0);
+    let (flags, args) = std::env::args().skip(1).partition::<Vec<_>, _>(|a| a.starts_with("--"));
+    // At most one of the solvers other than the local one can be selected.
+    let modes = ["--global", "--explicit", "--zielonka", "--promotion"];
+    let selected = flags.iter().filter(|f| modes.contains(&f.as_str())).collect::<Vec<_>>();
+    if let [first, second, ..] = selected[..] {
+        panic!("Conflicting flags {first} and {second}, select at most one solver");
+    }
+    // Statistics are only collected by the local solver.
+    let print_stats = flags.iter().any(|f| f == "--stats");
+    if let (Some(mode), true) = (selected.first(), print_stats) {
+        panic!("Conflicting flags {mode} and --stats, only the local solver collects statistics");
+    }
+    let global = selected.first().is_some_and(|&f| f == "--global");
+    // Explicit solvers, working directly on the parity game.
+    let explicit = selected.first().and_then(|f| match f.as_str() {
+        "--explicit" => Some(solve_parity_game::<ExplicitGame> as fn(&_) -> _),
+        "--zielonka" => Some(zielonka),
+        "--promotion" => Some(priority_promotion),
+        _ => None,
+    });
+    let switch_rule = flags
+        .iter()
+        .find_map(|f| f.strip_prefix("--switch="))
+        .map(|rule| match rule.split_once(':') {
+            Some(("random", seed)) => {
+                SwitchRule::random(seed.parse().expect("Failed to parse seed"))
+            }
+            None if rule == "best" => SwitchRule::Best,
+            None if rule == "greedy" => SwitchRule::Greedy,
+            None if rule == "single" => SwitchRule::Single,
+            _ => panic!("Unknown switch rule {rule}"),
+        })
+        .unwrap_or_default();
+
+    let mut args = args.into_iter();
+    let path = args.next().expect("No parity game file provided");
+    let node = args.next().map(|n| n.parse().expect("Failed to parse starting node")).unwrap_or(0);
 
     let now = std::time::Instant::now();
 
     let file = std::fs::read_to_string(path).expect("Failed to read parity game file");
     let graph = parse_parity_game(&file).expect("Failed to parse parity game file");
 
+    if let Some(solve) = explicit {
+        println!("Parsing took {:?}", now.elapsed());
+
+        let now = std::time::Instant::now();
+        let solution = solve_explicit(&graph, solve);
+
+        println!("Solve took {:?}", now.elapsed());
+        let mut nodes = solution.into_iter().collect::<Vec<_>>();
+        nodes.sort_by_key(|&(node, _)| node);
+        for (node, winner) in nodes {
+            let winner = if winner == Player::P0 { 0 } else { 1 };
+            println!("Node {node}: player {winner}");
+        }
+        return;
+    }
+
     let (eqs, funs_formulas, node_id_to_var_id) = parity_game_to_fix(&graph);
-    let formulas = Rc::new(EqsFormulas::new(eqs, Rc::new(funs_formulas)));
+    let formulas = Shared::new(EqsFormulas::new(eqs, Shared::new(funs_formulas)));
     let init_b = BasisElemId(0);
     let init_v = node_id_to_var_id[&node];
 
@@ -26,8 +76,27 @@ fn main() {
 
     let now = std::time::Instant::now();
 
-    let is_winning = solve(init_b, init_v, formulas);
+    if global {
+        let solution = global::solve(1, formulas);
+
+        println!("Solve took {:?}", now.elapsed());
+        let mut nodes = node_id_to_var_id.iter().collect::<Vec<_>>();
+        nodes.sort();
+        for (node, &var) in nodes {
+            let winner = if solution[var].contains(&init_b) { 0 } else { 1 };
+            println!("Node {node}: player {winner}");
+        }
+        return;
+    }
+
+    let mut solver = LocalSolver::new(formulas);
+    solver.set_switch_rule(switch_rule);
+    let result = solver.solve(init_b, init_v);
+    let is_winning = result.is_winning();
 
     println!("Solve took {:?}", now.elapsed());
+    if print_stats {
+        println!("{}", result.stats);
+    }
     println!("Winner: player {}", if is_winning { 0 } else { 1 });
 }
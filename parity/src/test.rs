@@ -1,13 +1,14 @@
-use std::rc::Rc;
-
 use chumsky::error::Simple;
 use chumsky::primitive::{choice, just, none_of};
 use chumsky::text::{self, TextParser};
 use chumsky::Parser;
-use solver::local::solve;
+use solver::index::AsIndex;
+use solver::local::game::WinState;
+use solver::local::{solve, LocalSolver};
 use solver::strategy::Player;
 use solver::symbolic::compose::EqsFormulas;
 use solver::symbolic::formula::BasisElemId;
+use solver::sync::Shared;
 
 use crate::{parity_game_to_fix, parse_parity_game};
 
@@ -32,7 +33,7 @@ fn parse_parity_sol(source: &str) -> Result<Vec<(usize, Player)>, Vec<Simple<cha
 fn run_test(input: &str, sol: &str) {
     let game = parse_parity_game(input).unwrap();
     let (eqs, funs_formulas, node_id_to_var_id) = parity_game_to_fix(&game);
-    let formulas = Rc::new(EqsFormulas::new(eqs, Rc::new(funs_formulas)));
+    let formulas = Shared::new(EqsFormulas::new(eqs, Shared::new(funs_formulas)));
     let init_b = BasisElemId(0);
 
     let sol = parse_parity_sol(sol).unwrap();
@@ -40,25 +41,70 @@ fn run_test(input: &str, sol: &str) {
     for (n, winner) in sol {
         let init_v = node_id_to_var_id[&n];
 
-        let is_winning = solve(init_b, init_v, formulas.clone());
+        let is_winning = solve(init_b, init_v, formulas.clone()).is_winning();
         let expected_winning = winner == Player::P0;
 
         assert_eq!(is_winning, expected_winning);
     }
 }
 
-#[test]
-fn test_all() {
-    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/");
-    for e in std::fs::read_dir(dir).unwrap() {
-        let e = e.unwrap();
+/// Solves a node of the game, then solves again the nodes decided in the explored game after
+/// exporting it and parsing it back.
+fn run_export_test(input: &str) {
+    let game = parse_parity_game(input).unwrap();
+    let (eqs, funs_formulas, node_id_to_var_id) = parity_game_to_fix(&game);
+    let formulas = Shared::new(EqsFormulas::new(eqs, Shared::new(funs_formulas)));
+    let mut solver = LocalSolver::new(formulas);
+    solver.solve(BasisElemId(0), node_id_to_var_id[&0]);
+    let game = solver.game();
+
+    let mut exported = Vec::new();
+    game.write_pgsolver(&mut exported).unwrap();
+    let exported = parse_parity_game(std::str::from_utf8(&exported).unwrap()).unwrap();
+
+    let (eqs, funs_formulas, node_id_to_var_id) = parity_game_to_fix(&exported);
+    let formulas = Shared::new(EqsFormulas::new(eqs, Shared::new(funs_formulas)));
+    let mut exported_solver = LocalSolver::new(formulas);
+
+    let mut decided = 0;
+    for (p0, &n) in game.p0.ids.enumerate() {
+        let winner = match game.p0.win[p0] {
+            WinState::Win0 => Player::P0,
+            WinState::Win1 => Player::P1,
+            WinState::Unknown => continue,
+        };
+        let init_v = node_id_to_var_id[&n.to_usize()];
+        assert_eq!(exported_solver.solve(BasisElemId(0), init_v).winner(), winner);
+        decided += 1;
+    }
+    assert!(decided > 0);
+}
 
+fn test_files() -> impl Iterator<Item = (String, std::path::PathBuf)> {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/");
+    std::fs::read_dir(dir).unwrap().map(|e| e.unwrap()).filter_map(|e| {
         let name = e.file_name().into_string().unwrap();
         let path = e.path();
-        if name == ".gitignore" || path.extension() == Some("sol".as_ref()) {
-            continue;
+        let skip = name == ".gitignore" || path.extension() == Some("sol".as_ref());
+        (!skip).then_some((name, path))
+    })
+}
+
+#[test]
+fn export_round_trip() {
+    for (name, path) in test_files() {
+        let input = std::fs::read_to_string(&path).unwrap();
+
+        if let Err(e) = std::panic::catch_unwind(|| run_export_test(&input)) {
+            eprintln!("Test {name} failed");
+            std::panic::resume_unwind(e);
         }
+    }
+}
 
+#[test]
+fn test_all() {
+    for (name, path) in test_files() {
         let input = std::fs::read_to_string(&path).unwrap();
         let sol = std::fs::read_to_string(path.with_extension("sol")).unwrap();
 
@@ -0,0 +1,291 @@
+//! Independent checker for the certificates produced by the local solver.
+//!
+//! The checker only relies on the composed formulas of the positions and on a plain cycle
+//! analysis, so it can be trusted without trusting the strategy iteration.
+
+use std::fmt;
+
+use crate::local::Certificate;
+use crate::strategy::Player;
+use crate::symbolic::compose::EqsFormulas;
+use crate::symbolic::eq::FixType;
+use crate::symbolic::formula::{FormulaRef, Node};
+use crate::symbolic::moves::{P0Pos, P1Pos};
+use crate::sync::Shared;
+use crate::tarjan::Tarjan;
+use crate::Set;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheckError {
+    /// Player 0 strategy has no move for a reachable position.
+    MissingP0Move(P0Pos),
+    /// Player 1 strategy has no move for a reachable position.
+    MissingP1Move(P1Pos),
+    /// The move chosen by player 0 does not satisfy the formula of its position.
+    InvalidP0Move(P0Pos, P1Pos),
+    /// The move chosen by player 1 is not one of the positions it can choose.
+    InvalidP1Move(P1Pos, P0Pos),
+    /// The opponent can force an infinite play where the given position is the most relevant
+    /// one visited infinitely often, and its fixpoint type makes the opponent win.
+    LosingCycle(P0Pos),
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::MissingP0Move(p) => write!(f, "no move for player 0 at {p:?}"),
+            CheckError::MissingP1Move(p) => write!(f, "no move for player 1 at {p:?}"),
+            CheckError::InvalidP0Move(p, m) => {
+                write!(f, "invalid move {m:?} for player 0 at {p:?}")
+            }
+            CheckError::InvalidP1Move(p, m) => {
+                write!(f, "invalid move {m:?} for player 1 at {p:?}")
+            }
+            CheckError::LosingCycle(p) => write!(f, "the opponent can force a cycle through {p:?}"),
+        }
+    }
+}
+
+impl std::error::Error for CheckError {}
+
+/// Checks that every play starting from `init` and consistent with the strategy in
+/// `certificate` is won by the player that the certificate claims to be winning.
+pub fn check(
+    formulas: &EqsFormulas,
+    init: P0Pos,
+    certificate: &Certificate,
+) -> Result<(), CheckError> {
+    let (positions, succs) = match certificate {
+        Certificate::P0(moves) => reachable(init, |pos| {
+            let mov = moves.get(&pos).ok_or(CheckError::MissingP0Move(pos))?;
+            let chosen = mov.moves.iter().copied().collect::<Set<_>>();
+            let f = formulas.get(pos.b, pos.i);
+            if !satisfies(&f, &chosen) {
+                return Err(CheckError::InvalidP0Move(pos, mov.clone()));
+            }
+            // Player 1 can then choose any of the positions in the move.
+            Ok(chosen.into_iter().collect())
+        })?,
+        Certificate::P1(moves) => reachable(init, |pos| {
+            // Player 0 can choose any minimal move, and player 1 must answer to all of them.
+            let mut succs = Vec::new();
+            let f = formulas.get(pos.b, pos.i);
+            for mov in dnf(&f) {
+                let next =
+                    *moves.get(&mov).ok_or_else(|| CheckError::MissingP1Move(mov.clone()))?;
+                if !mov.moves.contains(&next) {
+                    return Err(CheckError::InvalidP1Move(mov, next));
+                }
+                succs.push(next);
+            }
+            Ok(succs)
+        })?,
+    };
+
+    let winner = certificate.winner();
+    let favours = |pos: &P0Pos| match formulas.eq_fix_type(pos.i) {
+        FixType::Max => Player::P0,
+        FixType::Min => Player::P1,
+    };
+
+    match losing_cycle(&positions, &succs, |p| favours(p) == winner) {
+        Some(pos) => Err(CheckError::LosingCycle(pos)),
+        None => Ok(()),
+    }
+}
+
+/// Explores the positions reachable from `init`, where `succs_of` gives the player 0 positions
+/// reachable in one round, and returns them with the successors of each of them.
+fn reachable(
+    init: P0Pos,
+    mut succs_of: impl FnMut(P0Pos) -> Result<Vec<P0Pos>, CheckError>,
+) -> Result<(Set<P0Pos>, Vec<Vec<usize>>), CheckError> {
+    let mut positions = Set::from_iter([init]);
+    let mut succs = Vec::new();
+
+    while succs.len() < positions.len() {
+        let next = succs_of(positions[succs.len()])?;
+        succs.push(next.into_iter().map(|p| positions.insert_full(p).0).collect());
+    }
+
+    Ok((positions, succs))
+}
+
+fn satisfies(f: &FormulaRef, chosen: &Set<P0Pos>) -> bool {
+    match *f.node() {
+        Node::Atom(b, i) => chosen.contains(&P0Pos { b, i }),
+        Node::And(ref children) => children.iter().all(|f| satisfies(f, chosen)),
+        Node::Or(ref children) => children.iter().any(|f| satisfies(f, chosen)),
+    }
+}
+
+/// Expands the formula into its disjunctive normal form, returning each conjunction as a move
+/// unless it contains another one. Player 1 can answer the larger moves like the smaller ones.
+fn dnf(f: &FormulaRef) -> Vec<P1Pos> {
+    fn terms(f: &FormulaRef) -> Vec<Vec<P0Pos>> {
+        match *f.node() {
+            Node::Atom(b, i) => vec![vec![P0Pos { b, i }]],
+            Node::And(ref children) => children.iter().fold(vec![Vec::new()], |acc, f| {
+                let terms = terms(f);
+                acc.iter()
+                    .flat_map(|prefix| terms.iter().map(move |term| [&prefix[..], term].concat()))
+                    .collect()
+            }),
+            Node::Or(ref children) => children.iter().flat_map(terms).collect(),
+        }
+    }
+
+    let mut terms = terms(f)
+        .into_iter()
+        .map(|mut moves| {
+            // Normalize the same way moves are normalized by the solver.
+            moves.sort_unstable_by_key(|&P0Pos { b, i }| (i, b));
+            moves.dedup();
+            moves
+        })
+        .collect::<Vec<_>>();
+    terms.sort_by_key(|moves| moves.len());
+
+    let mut minimal = Vec::<P1Pos>::new();
+    for moves in terms {
+        if !minimal.iter().any(|m| m.moves.iter().all(|p| moves.contains(p))) {
+            minimal.push(P1Pos { moves: Shared::from(moves) });
+        }
+    }
+    minimal
+}
+
+/// Searches for a cycle whose most relevant position is not `good`, returning that position.
+fn losing_cycle(
+    positions: &Set<P0Pos>,
+    succs: &[Vec<usize>],
+    good: impl Fn(&P0Pos) -> bool,
+) -> Option<P0Pos> {
+    let mut tarjan = Tarjan::new(succs);
+    let mut queue = vec![(0..positions.len()).collect::<Vec<_>>()];
+
+    // Each nontrivial SCC either has a bad most relevant variable, or all the cycles
+    // going through it are good and we can remove it and look at the remaining cycles.
+    while let Some(nodes) = queue.pop() {
+        for scc in tarjan.sccs(&nodes) {
+            let is_cycle = scc.len() > 1 || succs[scc[0]].contains(&scc[0]);
+            if !is_cycle {
+                continue;
+            }
+
+            let top = scc.iter().map(|&n| positions[n]).max_by_key(|p| p.i).unwrap();
+            if !good(&top) {
+                return Some(top);
+            }
+
+            queue.push(scc.into_iter().filter(|&n| positions[n].i != top.i).collect());
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, CheckError};
+    use crate::index::IndexedVec;
+    use crate::local::{Certificate, LocalSolver};
+    use crate::strategy::Player;
+    use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+    use crate::symbolic::eq::{Expr, FixEq, FixType, VarId};
+    use crate::symbolic::formula::BasisElemId;
+    use crate::symbolic::moves::{P0Pos, P1Pos};
+    use crate::sync::Shared;
+    use crate::Map;
+
+    fn formulas(eqs: Vec<FixEq>) -> EqsFormulas {
+        EqsFormulas::new(IndexedVec::from(eqs), Shared::new(FunsFormulas::new(IndexedVec::new())))
+    }
+
+    fn pos(i: usize) -> P0Pos {
+        P0Pos { b: BasisElemId(0), i: VarId(i) }
+    }
+
+    fn mov(is: &[usize]) -> P1Pos {
+        P1Pos { moves: is.iter().map(|&i| pos(i)).collect() }
+    }
+
+    #[test]
+    fn self_loop() {
+        let eq = |fix_type| FixEq { fix_type, expr: Expr::Var(VarId(0)) };
+        let nu = formulas(vec![eq(FixType::Max)]);
+        let mu = formulas(vec![eq(FixType::Min)]);
+
+        let p0 = Certificate::P0(Map::from_iter([(pos(0), mov(&[0]))]));
+        let p1 = Certificate::P1(Map::from_iter([(mov(&[0]), pos(0))]));
+
+        assert_eq!(check(&nu, pos(0), &p0), Ok(()));
+        assert_eq!(check(&mu, pos(0), &p0), Err(CheckError::LosingCycle(pos(0))));
+        assert_eq!(check(&mu, pos(0), &p1), Ok(()));
+        assert_eq!(check(&nu, pos(0), &p1), Err(CheckError::LosingCycle(pos(0))));
+    }
+
+    #[test]
+    fn invalid_moves() {
+        // X0 = X1 && X2, X1 = X0, X2 = true
+        let nu = formulas(vec![
+            FixEq {
+                fix_type: FixType::Max,
+                expr: Expr::And(vec![Expr::Var(VarId(1)), Expr::Var(VarId(2))]),
+            },
+            FixEq { fix_type: FixType::Max, expr: Expr::Var(VarId(0)) },
+            FixEq { fix_type: FixType::Max, expr: Expr::TOP },
+        ]);
+
+        let missing = Certificate::P0(Map::from_iter([(pos(0), mov(&[1, 2]))]));
+        assert_eq!(check(&nu, pos(0), &missing), Err(CheckError::MissingP0Move(pos(1))));
+
+        let invalid = Certificate::P0(Map::from_iter([(pos(0), mov(&[1]))]));
+        assert_eq!(check(&nu, pos(0), &invalid), Err(CheckError::InvalidP0Move(pos(0), mov(&[1]))));
+
+        let p1 = Certificate::P1(Map::from_iter([(mov(&[1, 2]), pos(0))]));
+        assert_eq!(check(&nu, pos(0), &p1), Err(CheckError::InvalidP1Move(mov(&[1, 2]), pos(0))));
+
+        let valid = Certificate::P0(Map::from_iter([
+            (pos(0), mov(&[1, 2])),
+            (pos(1), mov(&[0])),
+            (pos(2), mov(&[])),
+        ]));
+        assert_eq!(check(&nu, pos(0), &valid), Ok(()));
+
+        let mut solver = LocalSolver::new(Shared::new(nu.clone()));
+        let result = solver.solve(BasisElemId(0), VarId(0));
+        let certificate = solver.certificate(result.init).unwrap();
+        assert_eq!(check(&nu, result.init, &certificate), Ok(()));
+    }
+
+    #[test]
+    fn solver_certificates() {
+        // X0 = X1 && X2, X1 = X0 || X2, X2 = X1 && X0, X3 = X3, X4 = X3 || X1
+        let var = |i| Expr::Var(VarId(i));
+        let formulas = formulas(vec![
+            FixEq { fix_type: FixType::Min, expr: Expr::And(vec![var(1), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(0), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::And(vec![var(1), var(0)]) },
+            FixEq { fix_type: FixType::Min, expr: var(3) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(3), var(1)]) },
+        ]);
+        let shared = Shared::new(formulas.clone());
+
+        // Both players must win some of the positions.
+        let mut winners = Vec::new();
+        let mut solver = LocalSolver::new(shared.clone());
+        for i in 0..5 {
+            let result = solver.solve(BasisElemId(0), VarId(i));
+            let certificate = solver.certificate(result.init).unwrap();
+            assert_eq!(check(&formulas, result.init, &certificate), Ok(()), "X{i}");
+            winners.push(result.winner());
+
+            let mut fresh = LocalSolver::new(shared.clone());
+            let result = fresh.solve(BasisElemId(0), VarId(i));
+            let certificate = fresh.certificate(result.init).unwrap();
+            assert_eq!(check(&formulas, result.init, &certificate), Ok(()), "X{i} (fresh)");
+        }
+        assert!(winners.contains(&Player::P0) && winners.contains(&Player::P1));
+    }
+}
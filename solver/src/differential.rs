@@ -0,0 +1,158 @@
+//! Differential testing of the local solver against the naive Kleene iteration on random
+//! systems, letting proptest shrink the failing ones.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::Union;
+
+use crate::check::check;
+use crate::global::solve_kleene;
+use crate::index::IndexedVec;
+use crate::local::LocalSolver;
+use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+use crate::symbolic::eq::{Expr, FixEq, FixType, FunId, VarId};
+use crate::symbolic::formula::{BasisElemId, Formula};
+use crate::symbolic::moves::MovesRepr;
+use crate::symbolic::simplify::simplify_sys;
+use crate::sync::Shared;
+
+#[derive(Clone, Debug)]
+struct System {
+    basis_count: usize,
+    eqs: Vec<FixEq>,
+    // The formula of each function for each basis element.
+    funs: Vec<Vec<Formula>>,
+}
+
+fn formula(basis_count: usize, arity: usize) -> BoxedStrategy<Formula> {
+    let constant = prop_oneof![Just(Formula::TRUE), Just(Formula::FALSE)];
+    let leaf = match arity {
+        0 => constant.boxed(),
+        _ => prop_oneof![
+            3 => (0..basis_count, 0..arity).prop_map(|(b, i)| Formula::Atom(BasisElemId(b), VarId(i))),
+            1 => constant,
+        ]
+        .boxed(),
+    };
+
+    leaf.prop_recursive(2, 8, 3, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..=3).prop_map(Formula::And),
+            vec(inner, 0..=3).prop_map(Formula::Or),
+        ]
+    })
+    .boxed()
+}
+
+fn expr(var_count: usize, arities: Vec<usize>) -> BoxedStrategy<Expr> {
+    let leaf = prop_oneof![
+        6 => (0..var_count).prop_map(|i| Expr::Var(VarId(i))),
+        1 => Just(Expr::TOP),
+        1 => Just(Expr::BOT),
+    ];
+
+    leaf.prop_recursive(3, 16, 3, move |inner| {
+        let mut options = vec![
+            vec(inner.clone(), 0..=3).prop_map(Expr::And).boxed(),
+            vec(inner.clone(), 0..=3).prop_map(Expr::Or).boxed(),
+        ];
+        for (f, &arity) in arities.iter().enumerate() {
+            let fun = vec(inner.clone(), arity).prop_map(move |args| Expr::Fun(FunId(f), args));
+            options.push(fun.boxed());
+        }
+        Union::new(options)
+    })
+    .boxed()
+}
+
+fn system() -> impl Strategy<Value = System> {
+    (1..=3usize, 1..=4usize, vec(0..=2usize, 0..=2)).prop_flat_map(
+        |(basis_count, vars, arities)| {
+            let funs = arities.iter().map(|&arity| vec(formula(basis_count, arity), basis_count));
+            let fix_type = prop_oneof![Just(FixType::Min), Just(FixType::Max)];
+            let eq = (fix_type, expr(vars, arities.clone()))
+                .prop_map(|(fix_type, expr)| FixEq { fix_type, expr });
+            (vec(eq, vars), funs.collect::<Vec<_>>()).prop_map(move |(eqs, funs)| System {
+                basis_count,
+                eqs,
+                funs,
+            })
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn local_matches_kleene(system in system()) {
+        let eqs = IndexedVec::from(system.eqs);
+        let funs = system.funs.into_iter().map(IndexedVec::from).collect();
+        let funs = FunsFormulas::new(funs);
+
+        let expected = solve_kleene(system.basis_count, &eqs, &funs);
+
+        let formulas = Shared::new(EqsFormulas::new(eqs, Shared::new(funs)));
+        let mut solver = LocalSolver::new(formulas.clone());
+        for (i, solution) in expected.enumerate() {
+            for b in (0..system.basis_count).map(BasisElemId) {
+                let winning = solution.contains(&b);
+
+                let mut fresh = LocalSolver::new(formulas.clone());
+                let result = fresh.solve(b, i);
+                prop_assert_eq!(result.is_winning(), winning, "{:?} {:?}", b, i);
+                let certificate = fresh.certificate(result.init).unwrap();
+                prop_assert_eq!(check(&formulas, result.init, &certificate), Ok(()));
+
+                // Also with a solver reusing the game of the previous queries.
+                let result = solver.solve(b, i);
+                prop_assert_eq!(result.is_winning(), winning, "{:?} {:?} (shared)", b, i);
+                let certificate = solver.certificate(result.init).unwrap();
+                prop_assert_eq!(check(&formulas, result.init, &certificate), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn bdd_moves_match_kleene(system in system()) {
+        let eqs = IndexedVec::from(system.eqs);
+        let funs = system.funs.into_iter().map(IndexedVec::from).collect();
+        let funs = FunsFormulas::new(funs);
+
+        let expected = solve_kleene(system.basis_count, &eqs, &funs);
+
+        let mut formulas = EqsFormulas::new(eqs, Shared::new(funs));
+        formulas.set_moves_repr(MovesRepr::Bdd);
+        let formulas = Shared::new(formulas);
+        let mut solver = LocalSolver::new(formulas.clone());
+        for (i, solution) in expected.enumerate() {
+            for b in (0..system.basis_count).map(BasisElemId) {
+                let winning = solution.contains(&b);
+
+                let result = solver.solve(b, i);
+                prop_assert_eq!(result.is_winning(), winning, "{:?} {:?}", b, i);
+                let certificate = solver.certificate(result.init).unwrap();
+                prop_assert_eq!(check(&formulas, result.init, &certificate), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn simplify_preserves_solutions(system in system()) {
+        let eqs = IndexedVec::from(system.eqs);
+        let funs = system.funs.into_iter().map(IndexedVec::from).collect();
+        let funs = FunsFormulas::new(funs);
+
+        let expected = solve_kleene(system.basis_count, &eqs, &funs);
+
+        for root in eqs.indexes() {
+            let (simplified, vars) = simplify_sys(&eqs, root);
+            prop_assert!(vars[root].is_some());
+
+            let solution = solve_kleene(system.basis_count, &simplified, &funs);
+            for (var, new) in vars.enumerate() {
+                if let Some(new) = *new {
+                    prop_assert_eq!(&solution[new], &expected[var], "{:?} from {:?}", var, root);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Global solving, computing the whole solution of a system over a finite basis.
+
+use crate::index::{AsIndex, IndexedVec};
+use crate::local::LocalSolver;
+use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+use crate::symbolic::eq::{Expr, FixEq, FixType, VarId};
+use crate::symbolic::formula::{BasisElemId, Formula};
+use crate::sync::Shared;
+use crate::Set;
+
+/// Computes, for every variable, the set of basis elements under its solution, assuming the
+/// basis elements are the ones with index less than `basis_count`.
+///
+/// All the queries share the same game, so positions decided while answering one of them
+/// are not explored again for the following ones.
+pub fn solve(
+    basis_count: usize,
+    formulas: Shared<EqsFormulas>,
+) -> IndexedVec<VarId, Set<BasisElemId>> {
+    let var_count = formulas.var_count();
+    let mut solver = LocalSolver::new(formulas);
+
+    (0..var_count)
+        .map(|i| {
+            (0..basis_count)
+                .map(BasisElemId)
+                .filter(|&b| solver.solve(b, VarId(i)).is_winning())
+                .collect()
+        })
+        .collect()
+}
+
+/// Computes the same solution as [`solve`] by naive nested Kleene iteration, directly
+/// interpreting the equations and the formulas of the functions.
+///
+/// The last equation is the outermost one. Every inner fixpoint is computed again from scratch
+/// for each iteration of the outer ones, so this takes exponential time in the number of
+/// equations and is only meant as a reference for small systems.
+pub fn solve_kleene(
+    basis_count: usize,
+    eqs: &IndexedVec<VarId, FixEq>,
+    funs: &FunsFormulas,
+) -> IndexedVec<VarId, Set<BasisElemId>> {
+    let mut env = eqs.iter().map(|_| Set::default()).collect();
+    Kleene { basis_count, eqs, funs }.solve_inner(eqs.len(), &mut env);
+    env
+}
+
+struct Kleene<'a> {
+    basis_count: usize,
+    eqs: &'a IndexedVec<VarId, FixEq>,
+    funs: &'a FunsFormulas,
+}
+
+impl Kleene<'_> {
+    // Solves the equations before `k`, given the values of the others in `env`.
+    fn solve_inner(&self, k: usize, env: &mut IndexedVec<VarId, Set<BasisElemId>>) {
+        let Some(i) = k.checked_sub(1).map(VarId) else { return };
+
+        env[i] = match self.eqs[i].fix_type {
+            FixType::Min => Set::default(),
+            FixType::Max => (0..self.basis_count).map(BasisElemId).collect(),
+        };
+
+        loop {
+            self.solve_inner(i.to_usize(), env);
+
+            let next = (0..self.basis_count)
+                .map(BasisElemId)
+                .filter(|&b| self.eval_expr(&self.eqs[i].expr, b, env))
+                .collect::<Set<_>>();
+            if next == env[i] {
+                break;
+            }
+            env[i] = next;
+        }
+    }
+
+    // Whether `b` is under the value of `expr` in `env`.
+    fn eval_expr(
+        &self,
+        expr: &Expr,
+        b: BasisElemId,
+        env: &IndexedVec<VarId, Set<BasisElemId>>,
+    ) -> bool {
+        match expr {
+            Expr::Var(i) => env[*i].contains(&b),
+            Expr::And(exprs) => exprs.iter().all(|e| self.eval_expr(e, b, env)),
+            Expr::Or(exprs) => exprs.iter().any(|e| self.eval_expr(e, b, env)),
+            Expr::Fun(f, args) => self.eval_formula(&self.funs.get(b, *f), args, env),
+        }
+    }
+
+    // Whether the formula of a function holds, with its atoms referring to the arguments.
+    fn eval_formula(
+        &self,
+        formula: &Formula,
+        args: &[Expr],
+        env: &IndexedVec<VarId, Set<BasisElemId>>,
+    ) -> bool {
+        match formula {
+            Formula::Atom(b, i) => self.eval_expr(&args[i.to_usize()], *b, env),
+            Formula::And(fs) => fs.iter().all(|f| self.eval_formula(f, args, env)),
+            Formula::Or(fs) => fs.iter().any(|f| self.eval_formula(f, args, env)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, solve_kleene};
+    use crate::index::IndexedVec;
+    use crate::local::LocalSolver;
+    use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+    use crate::symbolic::eq::{Expr, FixEq, FixType, FunId, VarId};
+    use crate::symbolic::formula::{BasisElemId, Formula};
+    use crate::sync::Shared;
+    use crate::Set;
+
+    fn new_system() -> (IndexedVec<VarId, FixEq>, FunsFormulas) {
+        // Transitions 0 -> 1, 1 -> 0, 1 -> 2, 2 -> 2, with p holding only in 0.
+        let succs = [vec![1], vec![0, 2], vec![2]];
+        let modality = |f: fn(Vec<Formula>) -> Formula| {
+            let formulas = succs
+                .iter()
+                .map(|ts| f(ts.iter().map(|&t| Formula::Atom(BasisElemId(t), VarId(0))).collect()));
+            formulas.collect::<IndexedVec<BasisElemId, _>>()
+        };
+        let p = IndexedVec::from(vec![Formula::TRUE, Formula::FALSE, Formula::FALSE]);
+        let funs = FunsFormulas::new(IndexedVec::from(vec![
+            modality(Formula::Or),
+            modality(Formula::And),
+            p,
+        ]));
+
+        let (diamond, square, p) = (FunId(0), FunId(1), Expr::Fun(FunId(2), Vec::new()));
+        let var = |i| Expr::Var(VarId(i));
+        let eqs = IndexedVec::from(vec![
+            // X0 = (p && <>X1) || <>X0
+            FixEq {
+                fix_type: FixType::Min,
+                expr: Expr::Or(vec![
+                    Expr::And(vec![p.clone(), Expr::Fun(diamond, vec![var(1)])]),
+                    Expr::Fun(diamond, vec![var(0)]),
+                ]),
+            },
+            // X1 = X0, so there's a path visiting p infinitely often
+            FixEq { fix_type: FixType::Max, expr: var(0) },
+            // X2 = p || []X2, so p is eventually reached on every path
+            FixEq {
+                fix_type: FixType::Min,
+                expr: Expr::Or(vec![p.clone(), Expr::Fun(square, vec![var(2)])]),
+            },
+            // X3 = X2 || (<>X3 && []X1)
+            FixEq {
+                fix_type: FixType::Max,
+                expr: Expr::Or(vec![
+                    var(2),
+                    Expr::And(vec![
+                        Expr::Fun(diamond, vec![var(3)]),
+                        Expr::Fun(square, vec![var(1)]),
+                    ]),
+                ]),
+            },
+        ]);
+        (eqs, funs)
+    }
+
+    #[test]
+    fn kleene_matches_local() {
+        let (eqs, funs) = new_system();
+        let expected = solve_kleene(3, &eqs, &funs);
+        let set = |bs: &[usize]| bs.iter().map(|&b| BasisElemId(b)).collect::<Set<_>>();
+        assert_eq!(expected[VarId(1)], set(&[0, 1]));
+        assert_eq!(expected[VarId(2)], set(&[0]));
+
+        let formulas = Shared::new(EqsFormulas::new(eqs, Shared::new(funs)));
+        assert_eq!(*solve(3, formulas), *expected);
+    }
+
+    #[test]
+    fn shared_game_matches_single_queries() {
+        let (eqs, funs) = new_system();
+        let var_count = eqs.len();
+        let formulas = Shared::new(EqsFormulas::new(eqs, Shared::new(funs)));
+        let solution = solve(3, formulas.clone());
+
+        // Each query is answered by a fresh solver, exploring its own game from scratch.
+        for i in (0..var_count).map(VarId) {
+            for b in (0..3).map(BasisElemId) {
+                let is_winning = LocalSolver::new(formulas.clone()).solve(b, i).is_winning();
+                assert_eq!(solution[i].contains(&b), is_winning, "{i:?} {b:?}");
+            }
+        }
+    }
+}
@@ -33,21 +33,17 @@ impl<I: AsIndex, T> IndexedVec<I, T> {
         index
     }
 
-    pub fn into_enumerate(
-        self,
-    ) -> impl Iterator<Item = (I, T)> + DoubleEndedIterator + ExactSizeIterator {
+    pub fn into_enumerate(self) -> impl DoubleEndedIterator<Item = (I, T)> + ExactSizeIterator {
         self.vec.into_iter().enumerate().map(|(i, t)| (I::from_usize(i), t))
     }
 
-    pub fn enumerate(
-        &self,
-    ) -> impl Iterator<Item = (I, &T)> + DoubleEndedIterator + ExactSizeIterator {
+    pub fn enumerate(&self) -> impl DoubleEndedIterator<Item = (I, &T)> + ExactSizeIterator {
         self.vec.iter().enumerate().map(|(i, t)| (I::from_usize(i), t))
     }
 
     pub fn enumerate_mut(
         &mut self,
-    ) -> impl Iterator<Item = (I, &mut T)> + DoubleEndedIterator + ExactSizeIterator {
+    ) -> impl DoubleEndedIterator<Item = (I, &mut T)> + ExactSizeIterator {
         self.vec.iter_mut().enumerate().map(|(i, t)| (I::from_usize(i), t))
     }
 
@@ -1,8 +1,15 @@
+pub mod check;
+#[cfg(test)]
+mod differential;
+pub mod global;
 pub mod index;
 pub mod local;
+pub mod reference;
 mod retain;
 pub mod strategy;
 pub mod symbolic;
+pub mod sync;
+mod tarjan;
 
 pub type Set<T> = indexmap::IndexSet<T, rustc_hash::FxBuildHasher>;
 pub type Map<K, V> = rustc_hash::FxHashMap<K, V>;
@@ -0,0 +1,297 @@
+use crate::index::{AsIndex, IndexedVec};
+use crate::reference::zielonka;
+use crate::strategy::{GetRelevance, NodeId, ParityGraph, Player, Relevance};
+use crate::symbolic::formula::{FormulaRef, Node};
+use crate::symbolic::moves::{P0Pos, P1Pos};
+use crate::sync::Shared;
+use crate::{Map, Set};
+
+use super::game::{Game, NodeP0Id, WinState};
+
+/// Strategy of the winning player, restricted to the positions reachable from the initial one
+/// when the winning player follows it.
+#[derive(Clone, Debug)]
+pub enum Certificate {
+    /// Player 0 wins by moving from each of its positions to the given player 1 position.
+    P0(Map<P0Pos, P1Pos>),
+    /// Player 1 wins by moving from each of its positions to the given player 0 position.
+    P1(Map<P1Pos, P0Pos>),
+}
+
+impl Certificate {
+    pub fn winner(&self) -> Player {
+        match self {
+            Certificate::P0(_) => Player::P0,
+            Certificate::P1(_) => Player::P1,
+        }
+    }
+}
+
+// Note: a strategy obtained by blindly moving to nodes known to be winning is not necessarily
+// winning, since it could create cycles whose most relevant node favours the opponent.
+// Instead the moves of player 0 always go to nodes that were won no later than the current one,
+// and nodes won together follow the same subgame strategy, so every cycle is one of those
+// strategies' cycles. Player 1 strategies are instead computed again from the winning regions.
+impl Game {
+    /// Builds the certificate for a decided node from the moves recorded when nodes were won,
+    /// or from the positions won by player 1.
+    pub fn certificate(&self, init: NodeP0Id) -> Certificate {
+        match self.p0.win[init] {
+            WinState::Win0 => Certificate::P0(self.p0_certificate(init)),
+            WinState::Win1 => Certificate::P1(self.p1_certificate(init)),
+            WinState::Unknown => panic!("Certificate requested for an undecided node"),
+        }
+    }
+
+    fn p0_certificate(&self, init: NodeP0Id) -> Map<P0Pos, P1Pos> {
+        let mut moves = Map::default();
+        let mut queue = vec![self.p0.pos[init]];
+
+        while let Some(pos) = queue.pop() {
+            if moves.contains_key(&pos) {
+                continue;
+            }
+
+            // Player 0 follows the move that made it win, which leads to a node where it wins.
+            let p0 = self.p0.pos.index_of(&pos);
+            let p1 = self.p0.win_move[&p0];
+            let mov = self.complete_move(p0, &self.p1.pos[p1]);
+
+            // Player 1 can choose any position, and it will be winning for player 0.
+            queue.extend(mov.moves());
+            moves.insert(pos, mov);
+        }
+
+        moves
+    }
+
+    fn p1_certificate(&self, init: NodeP0Id) -> Map<P1Pos, P0Pos> {
+        // The same move can be reached from many positions, including those for which it was
+        // never explored because simplifications have shown it to be losing, so the moves
+        // recorded for it are not necessarily winning for all of them. Instead solve the game
+        // where player 1 only moves to positions it is known to win, which it wins everywhere.
+        let game = ExplicitGame::new(self, self.p0.pos[init]);
+        let solution = zielonka(&game);
+        assert_eq!(solution.winner[NodeId(0)], Player::P1, "Certificate game not won by player 1");
+
+        let mut moves = Map::default();
+        let mut queue = vec![NodeId(0)];
+        let mut seen = Set::default();
+
+        while let Some(n) = queue.pop() {
+            if !seen.insert(n) {
+                continue;
+            }
+
+            match &game.nodes[n.to_usize()] {
+                // Player 0 can choose any move.
+                ExplicitPos::P0(_) => queue.extend(game.successors_of(n)),
+                ExplicitPos::P1(mov) => {
+                    let next = solution.strategy[n];
+                    let ExplicitPos::P0(pos) = game.nodes[next.to_usize()] else { unreachable!() };
+                    moves.insert(mov.clone(), pos);
+                    queue.push(next);
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Moves are simplified by removing positions already known to be winning for player 0.
+    /// Add them back, so that the move satisfies again the formula of `p0`.
+    fn complete_move(&self, p0: NodeP0Id, mov: &P1Pos) -> P1Pos {
+        // Only positions won before the last simplification can have been removed, and
+        // those were won before `p0`.
+        let allowed = |pos: P0Pos| mov.moves.contains(&pos) || self.simplified_away(p0, pos);
+
+        let P0Pos { b, i } = self.p0.pos[p0];
+        let mut moves = mov.moves.to_vec();
+        let f = self.formulas.get(b, i);
+        let found = witness(&f, &allowed, &mut moves);
+        assert!(found, "Winning move does not satisfy the formula");
+
+        // Normalize the same way the moves are normalized when generated.
+        moves.sort_unstable_by_key(|&P0Pos { b, i }| (i, b));
+        moves.dedup();
+        P1Pos { moves: Shared::from(moves) }
+    }
+
+    /// Whether `pos` was already won by player 0 when the moves of `p0` were last simplified.
+    fn simplified_away(&self, p0: NodeP0Id, pos: P0Pos) -> bool {
+        let time = self.simplified_w0[p0];
+        let p = self.p0.pos.get_index_of(&pos);
+        p.is_some_and(|p| self.p0.w0.get_index_of(&p).is_some_and(|pos_time| pos_time < time))
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum ExplicitPos {
+    P0(P0Pos),
+    P1(P1Pos),
+}
+
+/// The positions reachable from an initial one won by player 1, when player 1 only moves to
+/// positions known to be won by it.
+struct ExplicitGame {
+    nodes: Set<ExplicitPos>,
+    succs: IndexedVec<NodeId, Vec<NodeId>>,
+    preds: IndexedVec<NodeId, Vec<NodeId>>,
+    priorities: IndexedVec<NodeId, usize>,
+}
+
+impl ExplicitGame {
+    fn new(game: &Game, init: P0Pos) -> Self {
+        let mut nodes = Set::from_iter([ExplicitPos::P0(init)]);
+        let mut succs = IndexedVec::<NodeId, Vec<NodeId>>::new();
+        let mut priorities = IndexedVec::new();
+
+        while succs.len() < nodes.len() {
+            let n = NodeId(succs.len());
+            let (next, priority) = match &nodes[n.to_usize()] {
+                // Player 0 can choose any move, including those that were never explored.
+                ExplicitPos::P0(pos) => {
+                    let next = pos.moves(&game.formulas).map(ExplicitPos::P1).collect::<Vec<_>>();
+                    // Without moves player 0 loses, as in a self loop with an odd priority.
+                    match next.is_empty() {
+                        true => (Vec::new(), 1),
+                        false => (next, game.formulas.priorities().of(pos.i)),
+                    }
+                }
+                ExplicitPos::P1(mov) => {
+                    let won = |pos: &&P0Pos| {
+                        let p0 = game.p0.pos.get_index_of(*pos);
+                        p0.is_some_and(|p0| game.p0.win[p0] == WinState::Win1)
+                    };
+                    let next = mov.moves.iter().filter(won).copied().map(ExplicitPos::P0);
+                    let next = next.collect::<Vec<_>>();
+                    assert!(
+                        !next.is_empty(),
+                        "Losing move without a winning position for player 1"
+                    );
+                    (next, 0)
+                }
+            };
+
+            let next = next.into_iter().map(|p| NodeId(nodes.insert_full(p).0)).collect::<Vec<_>>();
+            succs.push(if next.is_empty() { vec![n] } else { next });
+            priorities.push(priority);
+        }
+
+        let mut preds = IndexedVec::from(vec![Vec::new(); succs.len()]);
+        for (n, next) in succs.enumerate() {
+            for &m in next {
+                preds[m].push(n);
+            }
+        }
+
+        Self { nodes, succs, preds, priorities }
+    }
+}
+
+impl GetRelevance for ExplicitGame {
+    fn relevance_of(&self, u: NodeId) -> Relevance {
+        Relevance { priority: self.priorities[u], node: u }
+    }
+}
+
+impl ParityGraph for ExplicitGame {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn player_of(&self, n: NodeId) -> Player {
+        match self.nodes[n.to_usize()] {
+            ExplicitPos::P0(_) => Player::P0,
+            ExplicitPos::P1(_) => Player::P1,
+        }
+    }
+
+    fn successors_of(&self, n: NodeId) -> impl Iterator<Item = NodeId> {
+        self.succs[n].iter().copied()
+    }
+
+    fn predecessors_of(&self, n: NodeId) -> impl Iterator<Item = NodeId> {
+        self.preds[n].iter().copied()
+    }
+
+    fn nodes_sorted_by_reward(&self) -> impl Iterator<Item = NodeId> {
+        let mut nodes = (0..self.nodes.len()).map(NodeId).collect::<Vec<_>>();
+        nodes.sort_by_key(|&n| self.reward_of(n));
+        nodes.into_iter()
+    }
+}
+
+/// Pushes to `out` a set of allowed positions that satisfies `f`, if one exists.
+fn witness(f: &FormulaRef, allowed: &impl Fn(P0Pos) -> bool, out: &mut Vec<P0Pos>) -> bool {
+    match *f.node() {
+        Node::Atom(b, i) => {
+            let found = allowed(P0Pos { b, i });
+            if found {
+                out.push(P0Pos { b, i });
+            }
+            found
+        }
+        Node::And(ref children) => children.iter().all(|f| witness(f, allowed, out)),
+        Node::Or(ref children) => children.iter().any(|f| {
+            let len = out.len();
+            witness(f, allowed, out) || {
+                out.truncate(len);
+                false
+            }
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::check::check;
+    use crate::index::IndexedVec;
+    use crate::local::game::WinState;
+    use crate::local::LocalSolver;
+    use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+    use crate::symbolic::eq::{Expr, FixEq, FixType, VarId};
+    use crate::symbolic::formula::BasisElemId;
+    use crate::sync::Shared;
+
+    #[test]
+    fn simplified_positions_have_moves() {
+        // X0 = X1 && X2, X1 = X0 || X2, X2 = X1 && X0, X3 = X3, X4 = X3 || X1
+        let var = |i| Expr::Var(VarId(i));
+        let eqs = IndexedVec::from(vec![
+            FixEq { fix_type: FixType::Min, expr: Expr::And(vec![var(1), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(0), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::And(vec![var(1), var(0)]) },
+            FixEq { fix_type: FixType::Min, expr: var(3) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(3), var(1)]) },
+        ]);
+        let funs = Shared::new(FunsFormulas::new(IndexedVec::new()));
+        let formulas = Shared::new(EqsFormulas::new(eqs, funs));
+
+        // X4 is explored only after X1 is won, so its moves are simplified.
+        let mut solver = LocalSolver::new(formulas.clone());
+        for i in [1, 0, 2, 3, 4] {
+            solver.solve(BasisElemId(0), VarId(i));
+        }
+        let game = solver.game();
+
+        // Completing a move adds back positions simplified away, which must have been won
+        // with a recorded move.
+        let mut simplified = 0;
+        for p0 in game.p0.ids.indexes() {
+            for (p, pos) in game.p0.ids.indexes().map(|p| (p, game.p0.pos[p])) {
+                if game.simplified_away(p0, pos) {
+                    assert_eq!(game.p0.win[p], WinState::Win0, "{pos:?}");
+                    assert!(game.p0.win_move.contains_key(&p), "{pos:?}");
+                    simplified += 1;
+                }
+            }
+        }
+        assert!(simplified > 0);
+
+        for &pos in game.p0.pos.iter() {
+            let certificate = solver.certificate(pos).unwrap();
+            assert_eq!(check(&formulas, pos, &certificate), Ok(()), "{pos:?}");
+        }
+    }
+}
@@ -9,8 +9,23 @@ pub fn update_winning_sets(
     final_strategy: &mut IndexedVec<NodeId, NodeId>,
     strategy: &mut GameStrategy,
 ) {
+    let losing = definitely_losing_set(game, profiles, final_strategy);
+
+    // Remember the moves of the subgame optimal strategies that lead to losing nodes, since
+    // those are the moves that let nodes winning together follow consistent strategies.
+    let subgame_moves = final_strategy
+        .enumerate()
+        .filter(|&(n, m)| losing.contains(m) && !losing.contains(&n))
+        .map(|(n, &m)| (game.resolve(n), game.resolve(m)))
+        .filter(|&(n, _)| match n {
+            NodeKind::P0(p0) => game.p0.win[p0] == WinState::Unknown,
+            NodeKind::P1(p1) => game.p1.win[p1] == WinState::Unknown,
+            _ => false,
+        })
+        .collect::<Vec<_>>();
+
     // Actually set the losing nodes as losing. This will also mark the predecessors as winning.
-    for n in definitely_losing_set(game, profiles, final_strategy) {
+    for &n in &losing {
         match game.resolve(n) {
             NodeKind::P0(p0) if game.p0.win[p0] == WinState::Unknown => {
                 game.set_p0_losing(p0, strategy, final_strategy)
@@ -21,6 +36,19 @@ pub fn update_winning_sets(
             _ => {}
         }
     }
+
+    // Nodes that became winning by moving to a losing node could have recorded a different one.
+    for (n, m) in subgame_moves {
+        match (n, m) {
+            (NodeKind::P0(p0), NodeKind::P1(p1)) if game.p0.win[p0] == WinState::Win0 => {
+                game.p0.win_move.insert(p0, p1);
+            }
+            (NodeKind::P1(p1), NodeKind::P0(p0)) if game.p1.win[p1] == WinState::Win1 => {
+                game.p1.win_move.insert(p1, p0);
+            }
+            _ => {}
+        }
+    }
 }
 
 fn definitely_losing_set(
@@ -7,26 +7,38 @@ use crate::local::game::WinState;
 use crate::strategy::{NodeId, PlayProfile, Player};
 use crate::symbolic::moves::Assumption;
 
-use super::game::{Game, GameStrategy, Inserted, NodeKind, NodeP1Id};
+use super::game::{Game, GameStrategy, Inserted, NodeKind, NodeP0Id, NodeP1Id};
+use super::limits::Limits;
+use super::policy::{Candidates, ExpansionPolicy};
 
 // Expand the game starting from nodes that are losing for the player controlling them.
 // Returns whether no improvement has occurred.
+#[allow(clippy::too_many_arguments)]
 pub fn expand(
     game: &mut Game,
     profiles: &mut IndexedVec<NodeId, PlayProfile>,
     final_strategy: &mut IndexedVec<NodeId, NodeId>,
     strategy: &mut GameStrategy,
+    init: NodeId,
     explore_goal: usize,
+    limits: &Limits,
+    policy: &mut dyn ExpansionPolicy,
 ) -> bool {
     let mut explored = 0;
     let mut improved = false;
     // Explore a minimum amount of nodes and at least until an improvement is found.
     while explored < explore_goal || !improved {
+        // Stop early if the limits are exceeded, the caller will then abort.
+        if limits.exceeded(game).is_some() {
+            return !improved;
+        }
+
         // Select starting node depending on who's currently winning.
-        let start = match profiles[NodeId::INIT].winning(game) {
-            Player::P0 => game.p1.incomplete.last().map(|&p1| game.p1.ids[p1]),
-            Player::P1 => game.p0.incomplete.last().map(|&p0| game.p0.ids[p0]),
-        };
+        let player = profiles[init].winning(game).opponent();
+        let start = policy.select_start(&Candidates { game, player }).map(|i| match player {
+            Player::P0 => game.p0.ids[game.p0.incomplete[i]],
+            Player::P1 => game.p1.ids[game.p1.incomplete[i]],
+        });
 
         // If there's no node to expand then return.
         let Some(start) = start else { return !improved };
@@ -43,6 +55,18 @@ pub fn expand(
                 Inserted::New(n) => n,
                 Inserted::Existing(n) => break n,
             };
+
+            // Leave player 0 nodes unexpanded if the path is long enough, they will be reached
+            // again when selected as starting nodes. Player 1 nodes are always expanded, since
+            // a node without successors needs to be known as winning for player 0.
+            if policy.max_path_len().is_some_and(|max| expanded.len() >= max) {
+                if let NodeKind::P0(p0) = game.resolve(n) {
+                    leave_unexpanded(p0, game, profiles, final_strategy, strategy);
+                    explored += 1;
+                    break n;
+                }
+            }
+
             expanded.insert(n);
             prev = n;
             next = expand_one(n, game, strategy).unwrap();
@@ -78,10 +102,10 @@ pub fn expand(
             NodeKind::W0 => game.set_p1_losing(prev.expect_p1(), strategy, final_strategy),
             NodeKind::W1 => game.set_p0_losing(prev.expect_p0(), strategy, final_strategy),
             NodeKind::P0(p0) if game.p0.win[p0] == WinState::Win1 => {
-                game.set_p1_winning(prev.expect_p1(), strategy, final_strategy);
+                game.set_p1_winning(prev.expect_p1(), p0, strategy, final_strategy);
             }
             NodeKind::P1(p1) if game.p1.win[p1] == WinState::Win0 => {
-                game.set_p0_winning(prev.expect_p0(), strategy, final_strategy);
+                game.set_p0_winning(prev.expect_p0(), p1, strategy, final_strategy);
             }
             _ => {}
         }
@@ -104,6 +128,7 @@ fn expand_one(n: NodeId, game: &mut Game, strategy: &mut GameStrategy) -> Option
                     None => Assumption::Unknown,
                 });
                 game.last_simplified[p0] = game.simplification_epoch();
+                game.simplified_w0[p0] = game.p0.w0.len();
             }
 
             let Some(pos) = game.p0.moves[p0].next() else {
@@ -150,6 +175,22 @@ fn expand_one(n: NodeId, game: &mut Game, strategy: &mut GameStrategy) -> Option
     }
 }
 
+// Gives a player 0 node without successors the same strategy and profile it would have if
+// all its moves were explored and found to be losing.
+fn leave_unexpanded(
+    p0: NodeP0Id,
+    game: &Game,
+    profiles: &mut IndexedVec<NodeId, PlayProfile>,
+    final_strategy: &mut IndexedVec<NodeId, NodeId>,
+    strategy: &mut GameStrategy,
+) {
+    strategy.try_add(p0, NodeP1Id::W1);
+    final_strategy.push(NodeId::W1);
+    profiles.resize_with(game.nodes.len(), PlayProfile::default);
+    profiles[game.p0.ids[p0]] =
+        PlayProfile { most_relevant: NodeId::W1, relevant_before: Vec::new(), count_before: 1 };
+}
+
 fn update_profiles(
     stop: NodeId,
     expanded: &IndexSet<NodeId>,
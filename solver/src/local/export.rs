@@ -0,0 +1,231 @@
+use std::io::{self, Write};
+
+use crate::index::{AsIndex, IndexedVec};
+use crate::strategy::{NodeId, Player, Strategy};
+use crate::symbolic::moves::{P0Pos, P1Pos};
+
+use super::game::{Game, GameStrategy, NodeKind, WinState};
+
+impl Game {
+    /// Writes the game explored so far as a parity game in the PGSolver format.
+    ///
+    /// Node identifiers are the `NodeId`s of the game, and each node is named after the
+    /// position it represents. Nodes with unexplored moves only have the successors explored
+    /// so far, while decided nodes point to the special nodes of their winner.
+    pub fn write_pgsolver(&self, w: &mut impl Write) -> io::Result<()> {
+        writeln!(w, "parity {};", self.nodes.len())?;
+
+        for n in (0..self.nodes.len()).map(NodeId) {
+            let priority = self.relevance_of(n).priority;
+            let owner = match self.player_of(n) {
+                Player::P0 => 0,
+                Player::P1 => 1,
+            };
+            let succs = self.successors_of(n).map(|m| m.to_usize().to_string()).collect::<Vec<_>>();
+            let name = self.node_name(n);
+            writeln!(w, "{} {priority} {owner} {} \"{name}\";", n.to_usize(), succs.join(","))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the game explored so far in the Graphviz DOT format.
+    ///
+    /// Player 0 nodes are drawn as ellipses and player 1 nodes as boxes, filled in green or red
+    /// when they are known to be winning for player 0 or player 1 respectively. Edges in
+    /// `strategy` are drawn in blue, edges in `final_strategy` are dashed, and edges in both
+    /// are drawn in bold.
+    pub fn write_dot(
+        &self,
+        w: &mut impl Write,
+        strategy: &GameStrategy,
+        final_strategy: &IndexedVec<NodeId, NodeId>,
+    ) -> io::Result<()> {
+        writeln!(w, "digraph {{")?;
+
+        for n in (0..self.nodes.len()).map(NodeId) {
+            let (shape, win) = match self.resolve(n) {
+                NodeKind::W0 | NodeKind::L1 => ("doubleoctagon", WinState::Win0),
+                NodeKind::W1 | NodeKind::L0 => ("doubleoctagon", WinState::Win1),
+                NodeKind::P0(p0) => ("ellipse", self.p0.win[p0]),
+                NodeKind::P1(p1) => ("box", self.p1.win[p1]),
+            };
+            let color = match win {
+                WinState::Unknown => "white",
+                WinState::Win0 => "palegreen",
+                WinState::Win1 => "lightpink",
+            };
+            let label = self.node_name(n);
+            writeln!(
+                w,
+                "  {} [label=\"{label}\", shape={shape}, style=filled, fillcolor={color}];",
+                n.to_usize()
+            )?;
+        }
+
+        for n in (0..self.nodes.len()).map(NodeId) {
+            let in_strategy = |m| match self.player_of(n) {
+                Player::P0 => strategy.get_direct(n, self) == m,
+                Player::P1 => false,
+            };
+            for m in self.successors_of(n) {
+                let style = match (in_strategy(m), final_strategy.get(n.to_usize()) == Some(&m)) {
+                    (true, true) => " [color=blue, style=bold]",
+                    (true, false) => " [color=blue]",
+                    (false, true) => " [style=dashed]",
+                    (false, false) => "",
+                };
+                writeln!(w, "  {} -> {}{style};", n.to_usize(), m.to_usize())?;
+            }
+        }
+
+        writeln!(w, "}}")
+    }
+
+    /// Human readable description of the node.
+    pub(super) fn node_name(&self, n: NodeId) -> String {
+        let p0_name = |P0Pos { b, i }: P0Pos| format!("(b{}, X{})", b.0, i.to_usize());
+        match self.resolve(n) {
+            NodeKind::W0 => "W0".to_string(),
+            NodeKind::L0 => "L0".to_string(),
+            NodeKind::W1 => "W1".to_string(),
+            NodeKind::L1 => "L1".to_string(),
+            NodeKind::P0(p0) => p0_name(self.p0.pos[p0]),
+            NodeKind::P1(p1) => {
+                let P1Pos { moves } = &self.p1.pos[p1];
+                let moves = moves.iter().map(|&pos| p0_name(pos)).collect::<Vec<_>>();
+                format!("[{}]", moves.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::{AsIndex, IndexedVec};
+    use crate::local::game::{GameStrategy, NodeKind, NodeP0Id, NodeP1Id, WinState};
+    use crate::local::LocalSolver;
+    use crate::strategy::test::parse_test;
+    use crate::strategy::{solve_parity_game, NodeId, Player, Strategy, StrategyMut};
+    use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+    use crate::symbolic::eq::{Expr, FixEq, FixType, VarId};
+    use crate::symbolic::formula::BasisElemId;
+    use crate::sync::Shared;
+
+    fn new_solver() -> LocalSolver {
+        // X0 = X1 && X2, X1 = X0 || X2, X2 = X1 && X0, X3 = X3, X4 = X3 || X1
+        let var = |i| Expr::Var(VarId(i));
+        let eqs = IndexedVec::from(vec![
+            FixEq { fix_type: FixType::Min, expr: Expr::And(vec![var(1), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(0), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::And(vec![var(1), var(0)]) },
+            FixEq { fix_type: FixType::Min, expr: var(3) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(3), var(1)]) },
+        ]);
+        let funs = Shared::new(FunsFormulas::new(IndexedVec::new()));
+        LocalSolver::new(Shared::new(EqsFormulas::new(eqs, funs)))
+    }
+
+    #[test]
+    fn pgsolver_keeps_winners() {
+        let mut solver = new_solver();
+        for i in 0..5 {
+            solver.solve(BasisElemId(0), VarId(i));
+        }
+
+        let mut exported = Vec::new();
+        solver.game().write_pgsolver(&mut exported).unwrap();
+        let exported = parse_test(std::str::from_utf8(&exported).unwrap());
+        let solution = solve_parity_game(&exported);
+
+        // The nodes decided by the local solver must have the same winner in the exported game.
+        let game = solver.game();
+        assert_eq!(solution.winner.len(), game.nodes.len());
+        for (p0, &n) in game.p0.ids.enumerate() {
+            let winner = match game.p0.win[p0] {
+                WinState::Win0 => Player::P0,
+                WinState::Win1 => Player::P1,
+                WinState::Unknown => continue,
+            };
+            assert_eq!(solution.winner[n], winner, "{:?}", game.p0.pos[p0]);
+        }
+    }
+
+    #[test]
+    fn dot_styles() {
+        let mut solver = new_solver();
+        for i in 0..5 {
+            solver.solve(BasisElemId(0), VarId(i));
+        }
+        let game = solver.game();
+
+        // Player 0 plays its last successor, while the final strategy takes the first one
+        // everywhere except in the proper player 0 nodes, which are left without a move.
+        let mut strategy = GameStrategy::new();
+        for p0 in game.p0.ids.indexes() {
+            strategy.try_add(p0, NodeP1Id::W1);
+        }
+        for p1 in game.p1.ids.indexes() {
+            strategy.try_add(NodeP0Id(0), p1);
+        }
+        strategy.update_each(game, |n, _| game.successors_of(n).last().unwrap());
+        let final_strategy = (0..game.nodes.len())
+            .map(NodeId)
+            .map(|n| match game.resolve(n) {
+                NodeKind::P0(_) => n,
+                _ => game.successors_of(n).next().unwrap(),
+            })
+            .collect::<IndexedVec<NodeId, _>>();
+
+        let mut dot = Vec::new();
+        game.write_dot(&mut dot, &strategy, &final_strategy).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        let line = |prefix: String| {
+            let found = dot.lines().find(|line| line.starts_with(&prefix));
+            found.unwrap_or_else(|| panic!("No line for {prefix}"))
+        };
+
+        let mut colors = Vec::new();
+        for n in (0..game.nodes.len()).map(NodeId) {
+            let node = line(format!("  {} [", n.to_usize()));
+            let (shape, win) = match game.resolve(n) {
+                NodeKind::P0(p0) => ("ellipse", Some(game.p0.win[p0])),
+                NodeKind::P1(p1) => ("box", Some(game.p1.win[p1])),
+                _ => ("doubleoctagon", None),
+            };
+            assert!(node.contains(&format!("shape={shape}")), "{node}");
+            if let Some(win) = win {
+                let color = match win {
+                    WinState::Unknown => "white",
+                    WinState::Win0 => "palegreen",
+                    WinState::Win1 => "lightpink",
+                };
+                assert!(node.contains(&format!("fillcolor={color}")), "{node}");
+                colors.push(color);
+            }
+        }
+        assert!(colors.contains(&"palegreen") && colors.contains(&"lightpink"));
+
+        let mut styles = Vec::new();
+        for n in (0..game.nodes.len()).map(NodeId) {
+            let played = match game.player_of(n) {
+                Player::P0 => Some(strategy.get_direct(n, game)),
+                Player::P1 => None,
+            };
+            for m in game.successors_of(n) {
+                let edge = line(format!("  {} -> {}", n.to_usize(), m.to_usize()));
+                let style = match (played == Some(m), final_strategy[n] == m) {
+                    (true, true) => " [color=blue, style=bold];",
+                    (true, false) => " [color=blue];",
+                    (false, true) => " [style=dashed];",
+                    (false, false) => ";",
+                };
+                assert!(edge.ends_with(&format!("{}{style}", m.to_usize())), "{edge}");
+                styles.push(style);
+            }
+        }
+        for style in [" [color=blue, style=bold];", " [color=blue];", " [style=dashed];"] {
+            assert!(styles.contains(&style), "No edge with{style}");
+        }
+    }
+}
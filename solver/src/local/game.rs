@@ -1,20 +1,18 @@
-use std::rc::Rc;
-
 use either::Either::{Left, Right};
 
 use crate::index::{new_index, AsIndex, IndexedSet, IndexedVec};
 use crate::strategy::{NodeId, Player, Relevance, Set};
 use crate::symbolic::compose::EqsFormulas;
-use crate::symbolic::eq::{FixType, VarId};
+use crate::symbolic::eq::VarId;
 use crate::symbolic::moves::{P0Moves, P0Pos, P1Moves, P1Pos};
+use crate::sync::Shared;
+use crate::Map;
 
 impl NodeId {
     pub const W0: NodeId = NodeId(0);
     pub const L0: NodeId = NodeId(1);
     pub const W1: NodeId = NodeId(2);
     pub const L1: NodeId = NodeId(3);
-
-    pub const INIT: NodeId = NodeId(4);
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -42,16 +40,12 @@ impl NodeKind {
 new_index!(pub index NodeP0Id);
 new_index!(pub index NodeP1Id);
 
-impl NodeP0Id {
-    pub const INIT: NodeP0Id = NodeP0Id(0);
-}
-
 impl NodeP1Id {
     pub const W1: NodeP1Id = NodeP1Id(usize::MAX);
     pub const L1: NodeP1Id = NodeP1Id(usize::MAX - 1);
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum WinState {
     Unknown,
     Win0,
@@ -78,11 +72,13 @@ pub struct NodesData<I, P, M, O> {
     pub w0: Set<I>,
     // Set of this player's nodes where player 1 wins
     pub w1: Set<I>,
+    // Move that makes this player win, recorded when the node is found to be winning.
+    pub win_move: Map<I, O>,
 }
 
 pub struct Game {
     // Formulas representing the equations in the system.
-    pub formulas: Rc<EqsFormulas>,
+    pub formulas: Shared<EqsFormulas>,
     // Data for player 0 nodes.
     pub p0: NodesData<NodeP0Id, P0Pos, P0Moves, NodeP1Id>,
     // Data for player 1 nodes.
@@ -94,12 +90,17 @@ pub struct Game {
     pub var_to_p0: IndexedVec<VarId, Vec<NodeP0Id>>,
 
     pub last_simplified: IndexedVec<NodeP0Id, usize>,
+    // Number of nodes won by player 0 when each node was last simplified, that is the prefix
+    // of `p0.w0` whose nodes may have been removed from its moves.
+    pub simplified_w0: IndexedVec<NodeP0Id, usize>,
+    // Number of edges ever inserted, including those removed once their nodes were decided.
+    pub explored_edges: usize,
 }
 
 impl Game {
-    pub fn new(init: P0Pos, formulas: Rc<EqsFormulas>) -> Self {
+    pub fn new(formulas: Shared<EqsFormulas>) -> Self {
         let var_count = formulas.var_count();
-        let mut game = Self {
+        Self {
             formulas,
             p0: NodesData::default(),
             p1: NodesData::default(),
@@ -107,11 +108,9 @@ impl Game {
             var_to_p0: IndexedVec::from(vec![Vec::new(); var_count]),
 
             last_simplified: IndexedVec::new(),
-        };
-
-        game.insert_p0(init);
-
-        game
+            simplified_w0: IndexedVec::new(),
+            explored_edges: 0,
+        }
     }
 
     pub fn resolve(&self, n: NodeId) -> NodeKind {
@@ -128,21 +127,24 @@ impl Game {
     pub fn relevance_of(&self, node: NodeId) -> Relevance {
         let priority = match self.resolve(node) {
             // High priority (higher than P0 nodes) in favour of P1
-            NodeKind::L0 | NodeKind::W1 => 2 * self.formulas.var_count() + 1,
+            NodeKind::L0 | NodeKind::W1 => self.max_p0_priority() + 1,
             // High priority (higher than P0 nodes) in favour of P0
-            NodeKind::W0 | NodeKind::L1 => 2 * self.formulas.var_count() + 2,
-            // Priority proportional to the equation/variable index, going from 1 to 2 * var_count
-            NodeKind::P0(n) => {
-                let i = self.p0.pos[n].i;
-                let fix_type = self.formulas.eq_fix_type(i);
-                2 * i.to_usize() + if fix_type == FixType::Max { 2 } else { 1 }
-            }
+            NodeKind::W0 | NodeKind::L1 => self.max_p0_priority() + 2,
+            // Priority given by the nesting of the variable in the system, going from 1 to
+            // one more than its alternation depth
+            NodeKind::P0(n) => self.formulas.priorities().of(self.p0.pos[n].i),
             // This is irrelevant
             NodeKind::P1(_) => 0,
         };
         Relevance { priority, node }
     }
 
+    // An even priority at least as high as the one of any P0 node.
+    fn max_p0_priority(&self) -> usize {
+        let max = self.formulas.priorities().max();
+        max + max % 2
+    }
+
     pub fn successors_of(&self, n: NodeId) -> impl Iterator<Item = NodeId> + '_ {
         match self.resolve(n) {
             // Successors of special nodes are only other special nodes.
@@ -186,23 +188,29 @@ impl Game {
     }
 
     pub fn nodes_sorted_by_reward(&self) -> impl Iterator<Item = NodeId> + '_ {
-        let iter = |fix_type| {
-            self.var_to_p0
-                .enumerate()
-                .filter(move |&(i, _)| self.formulas.eq_fix_type(i) == fix_type)
-                .flat_map(|(_, nodes)| nodes)
-                .map(|&n0| self.p0.ids[n0])
+        let priorities = self.formulas.priorities();
+        // P0 nodes with the given priority, sorted by node id.
+        let nodes_with = move |priority| {
+            let vars = priorities.vars_with(priority);
+            let nodes = vars.iter().flat_map(|&i| &self.var_to_p0[i]).map(|&n0| self.p0.ids[n0]);
+            let mut nodes = nodes.collect::<Vec<_>>();
+            // The nodes of each variable are already sorted.
+            if vars.len() > 1 {
+                nodes.sort_unstable();
+            }
+            nodes
         };
+        let (odd, even) = (1..=priorities.max()).partition::<Vec<_>, _>(|p| p % 2 == 1);
 
-        // Both have odd 2 * var_count + 1 relevance
+        // Both have the highest odd relevance
         let w1_nodes = [NodeId::W1, NodeId::L0].into_iter();
-        // These have odd >= 1 relevance and are sorted by decreasing node id.
-        let p0_f1_nodes = iter(FixType::Min).rev();
+        // These have odd >= 1 relevance and are sorted by decreasing relevance.
+        let p0_f1_nodes = odd.into_iter().rev().flat_map(move |p| nodes_with(p).into_iter().rev());
         // These have 0 reward.
         let p1_nodes = self.p1.ids.iter().copied();
-        // These have even >=2 reward and are sorted by node id.
-        let p0_f0_nodes = iter(FixType::Max);
-        // These have 2 * var_count + 2 reward
+        // These have even >=2 reward and are sorted by relevance.
+        let p0_f0_nodes = even.into_iter().flat_map(nodes_with);
+        // These have the highest even relevance
         let w0_nodes = [NodeId::W0, NodeId::L1].into_iter();
 
         w1_nodes.chain(p0_f1_nodes).chain(p1_nodes).chain(p0_f0_nodes).chain(w0_nodes)
@@ -227,6 +235,7 @@ impl Game {
 
         self.var_to_p0[pos.i].push(n);
         self.last_simplified.push(0);
+        self.simplified_w0.push(0);
 
         Inserted::New(n)
     }
@@ -252,12 +261,12 @@ impl Game {
 
     pub fn insert_p1_to_p0_edge(&mut self, pred: NodeP1Id, succ: NodeP0Id) {
         self.p0.preds[succ].insert(pred);
-        self.p1.succs[pred].insert(succ);
+        self.explored_edges += self.p1.succs[pred].insert(succ) as usize;
     }
 
     pub fn insert_p0_to_p1_edge(&mut self, pred: NodeP0Id, succ: NodeP1Id) {
         self.p1.preds[succ].insert(pred);
-        self.p0.succs[pred].insert(succ);
+        self.explored_edges += self.p0.succs[pred].insert(succ) as usize;
     }
 
     pub fn simplification_epoch(&self) -> usize {
@@ -340,6 +349,12 @@ impl GameStrategy {
     }
 }
 
+impl Default for GameStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<I, P, M, O> Default for NodesData<I, P, M, O> {
     fn default() -> Self {
         Self {
@@ -352,6 +367,7 @@ impl<I, P, M, O> Default for NodesData<I, P, M, O> {
             win: Default::default(),
             w0: Default::default(),
             w1: Default::default(),
+            win_move: Default::default(),
         }
     }
 }
@@ -7,7 +7,7 @@ use crate::strategy::{
 
 use super::game::{Game, GameStrategy, NodeKind, NodeP1Id};
 
-impl<'a> GetRelevance for Game {
+impl GetRelevance for Game {
     fn relevance_of(&self, u: NodeId) -> Relevance {
         (*self).relevance_of(u)
     }
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::game::Game;
+use super::stats::SolveStats;
+
+/// Limits on the resources a query to the local solver can use.
+///
+/// They are checked between the phases of the solver and between expansion steps, so they
+/// can be slightly exceeded before the query is aborted.
+#[derive(Clone, Debug, Default)]
+pub struct Limits {
+    /// Maximum number of nodes in the game, including those explored by previous queries.
+    pub max_nodes: Option<usize>,
+    /// Instant after which the query is aborted.
+    pub deadline: Option<Instant>,
+    /// Flag that aborts the query when set, possibly from another thread.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Limits {
+    pub(super) fn exceeded(&self, game: &Game) -> Option<AbortReason> {
+        if self.max_nodes.is_some_and(|max| game.p0.ids.len() + game.p1.ids.len() > max) {
+            return Some(AbortReason::NodeLimit);
+        }
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Some(AbortReason::Deadline);
+        }
+        if self.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+            return Some(AbortReason::Cancelled);
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbortReason {
+    NodeLimit,
+    Deadline,
+    Cancelled,
+}
+
+/// A query that was aborted before its position was decided.
+#[derive(Clone, Debug)]
+pub struct Aborted {
+    pub reason: AbortReason,
+    /// Statistics about the work done before aborting.
+    pub stats: Box<SolveStats>,
+}
+
+impl fmt::Display for Aborted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            AbortReason::NodeLimit => write!(f, "the game exceeded the maximum number of nodes"),
+            AbortReason::Deadline => write!(f, "the deadline was reached"),
+            AbortReason::Cancelled => write!(f, "the query was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Aborted {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    use super::{AbortReason, Limits};
+    use crate::index::IndexedVec;
+    use crate::local::LocalSolver;
+    use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+    use crate::symbolic::eq::{Expr, FixEq, FixType, VarId};
+    use crate::symbolic::formula::BasisElemId;
+    use crate::sync::Shared;
+
+    fn new_solver() -> LocalSolver {
+        // X0 = X1 && X2, X1 = X0 || X2, X2 = X1
+        let eqs = IndexedVec::from(vec![
+            FixEq {
+                fix_type: FixType::Min,
+                expr: Expr::And(vec![Expr::Var(VarId(1)), Expr::Var(VarId(2))]),
+            },
+            FixEq {
+                fix_type: FixType::Max,
+                expr: Expr::Or(vec![Expr::Var(VarId(0)), Expr::Var(VarId(2))]),
+            },
+            FixEq { fix_type: FixType::Max, expr: Expr::Var(VarId(1)) },
+        ]);
+        let funs = Shared::new(FunsFormulas::new(IndexedVec::new()));
+        LocalSolver::new(Shared::new(EqsFormulas::new(eqs, funs)))
+    }
+
+    #[test]
+    fn aborts_and_resumes() {
+        let (b, i) = (BasisElemId(0), VarId(0));
+
+        let mut solver = new_solver();
+        let limits = Limits { max_nodes: Some(1), ..Limits::default() };
+        let aborted = solver.solve_with_limits(b, i, &limits).unwrap_err();
+        assert_eq!(aborted.reason, AbortReason::NodeLimit);
+        assert!(aborted.stats.p0_nodes + aborted.stats.p1_nodes > 1);
+        assert!(solver.solve(b, i).is_winning());
+
+        let mut solver = new_solver();
+        let limits = Limits { deadline: Some(Instant::now()), ..Limits::default() };
+        let aborted = solver.solve_with_limits(b, i, &limits).unwrap_err();
+        assert_eq!(aborted.reason, AbortReason::Deadline);
+        assert!(solver.solve(b, i).is_winning());
+
+        let mut solver = new_solver();
+        let limits = Limits { cancel: Some(Arc::new(AtomicBool::new(true))), ..Limits::default() };
+        let aborted = solver.solve_with_limits(b, i, &limits).unwrap_err();
+        assert_eq!(aborted.reason, AbortReason::Cancelled);
+        assert!(solver.solve(b, i).is_winning());
+    }
+
+    #[test]
+    fn resumes_from_any_node_limit() {
+        for i in (0..3).map(VarId) {
+            let b = BasisElemId(0);
+            let winning = new_solver().solve(b, i).is_winning();
+
+            for max_nodes in 1..8 {
+                let mut solver = new_solver();
+                let limits = Limits { max_nodes: Some(max_nodes), ..Limits::default() };
+                let _ = solver.solve_with_limits(b, i, &limits);
+                assert_eq!(solver.solve(b, i).is_winning(), winning, "{i:?} {max_nodes}");
+            }
+        }
+    }
+}
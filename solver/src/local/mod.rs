@@ -1,8 +1,21 @@
+mod certificate;
 mod escape;
 mod expansion;
-mod game;
+mod export;
+pub mod game;
 mod impls;
+mod limits;
+#[cfg(feature = "sync")]
+mod parallel;
+mod policy;
 mod solve;
+mod stats;
 mod winning;
 
-pub use solve::solve;
+pub use certificate::Certificate;
+pub use limits::{AbortReason, Aborted, Limits};
+#[cfg(feature = "sync")]
+pub use parallel::solve_many;
+pub use policy::{Candidates, DefaultExpansion, ExpansionPolicy};
+pub use solve::{solve, solve_with_limits, LocalSolver, SolveResult, Step};
+pub use stats::SolveStats;
@@ -0,0 +1,76 @@
+use std::thread;
+
+use crate::symbolic::compose::EqsFormulas;
+use crate::symbolic::eq::VarId;
+use crate::symbolic::formula::BasisElemId;
+use crate::sync::Shared;
+
+use super::solve::{LocalSolver, SolveResult};
+
+/// Solves the given positions using up to `threads` threads, returning the results in the
+/// same order. Each thread solves a contiguous chunk of the queries with its own
+/// [`LocalSolver`], so only the formulas and their caches are shared between threads.
+///
+/// Formulas missing from the caches are composed while holding the lock of the shared formula
+/// DAG, so threads exploring new positions at the same time wait for each other. The speedup
+/// is thus limited while the caches are still cold, and grows as the queries reuse formulas.
+pub fn solve_many(
+    queries: &[(BasisElemId, VarId)],
+    formulas: Shared<EqsFormulas>,
+    threads: usize,
+) -> Vec<SolveResult> {
+    if queries.is_empty() {
+        return Vec::new();
+    }
+
+    let chunk_size = queries.len().div_ceil(threads.max(1));
+
+    thread::scope(|s| {
+        let handles = queries
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let formulas = formulas.clone();
+                s.spawn(move || {
+                    let mut solver = LocalSolver::new(formulas);
+                    chunk.iter().map(|&(b, i)| solver.solve(b, i)).collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::solve_many;
+    use crate::index::IndexedVec;
+    use crate::local::solve;
+    use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+    use crate::symbolic::eq::{Expr, FixEq, FixType, VarId};
+    use crate::symbolic::formula::BasisElemId;
+    use crate::sync::Shared;
+
+    #[test]
+    fn same_as_sequential() {
+        // X0 = X1 && X2, X1 = X0 || X2, X2 = X1 && X0, X3 = X3
+        let var = |i| Expr::Var(VarId(i));
+        let eqs = IndexedVec::from(vec![
+            FixEq { fix_type: FixType::Min, expr: Expr::And(vec![var(1), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(0), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::And(vec![var(1), var(0)]) },
+            FixEq { fix_type: FixType::Min, expr: var(3) },
+        ]);
+        let funs = Shared::new(FunsFormulas::new(IndexedVec::new()));
+        let formulas = Shared::new(EqsFormulas::new(eqs, funs));
+
+        let queries = (0..4).map(|i| (BasisElemId(0), VarId(i))).collect::<Vec<_>>();
+        let results = solve_many(&queries, formulas.clone(), 3);
+
+        assert_eq!(results.len(), queries.len());
+        for (result, &(b, i)) in results.iter().zip(&queries) {
+            assert_eq!(result.init.i, i);
+            assert_eq!(result.winner(), solve(b, i, formulas.clone()).winner());
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::strategy::Player;
+use crate::symbolic::moves::{P0Pos, P1Pos};
+
+use super::game::{Game, NodeP0Id, NodeP1Id};
+
+/// Controls how the local solver explores the game.
+pub trait ExpansionPolicy {
+    /// Minimum number of nodes to explore in the given expansion round of a query, starting
+    /// from round 0. Each round continues anyway until the current strategy is improved.
+    fn explore_goal(&mut self, round: usize) -> usize {
+        1usize.checked_shl(round as u32).unwrap_or(usize::MAX)
+    }
+
+    /// Picks the node to start an expansion from, returning its index in `candidates`.
+    /// Returning `None` stops the current round.
+    fn select_start(&mut self, candidates: &Candidates<'_>) -> Option<usize> {
+        candidates.len().checked_sub(1)
+    }
+
+    /// Maximum number of new nodes to expand along a single path before selecting a new
+    /// starting node. Paths are only cut at player 0 nodes, which are considered losing for
+    /// player 0 until they are picked as starting nodes. `None` extends each path until it
+    /// reaches an existing node, which resembles a depth-first search, while small values
+    /// resemble a breadth-first search.
+    fn max_path_len(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// The policy used by default: the goal doubles at every round, the most recently found
+/// incomplete node is expanded first and paths are extended until they reach an existing node.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultExpansion;
+
+impl ExpansionPolicy for DefaultExpansion {}
+
+/// Nodes with unexplored moves that can be used to start an expansion. They all belong to
+/// the player that is currently losing on the initial position, since only its nodes can
+/// improve its strategy.
+pub struct Candidates<'a> {
+    pub(super) game: &'a Game,
+    pub(super) player: Player,
+}
+
+impl Candidates<'_> {
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    pub fn len(&self) -> usize {
+        match self.player {
+            Player::P0 => self.game.p0.incomplete.len(),
+            Player::P1 => self.game.p1.incomplete.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The position of the candidate, if it belongs to player 0.
+    pub fn p0_pos(&self, index: usize) -> Option<P0Pos> {
+        match self.player {
+            Player::P0 => Some(self.game.p0.pos[self.p0(index)]),
+            Player::P1 => None,
+        }
+    }
+
+    /// The position of the candidate, if it belongs to player 1.
+    pub fn p1_pos(&self, index: usize) -> Option<&P1Pos> {
+        match self.player {
+            Player::P0 => None,
+            Player::P1 => Some(&self.game.p1.pos[self.p1(index)]),
+        }
+    }
+
+    /// The priority of the candidate in the parity game, which is always 0 for player 1 nodes.
+    pub fn priority(&self, index: usize) -> usize {
+        let node = match self.player {
+            Player::P0 => self.game.p0.ids[self.p0(index)],
+            Player::P1 => self.game.p1.ids[self.p1(index)],
+        };
+        self.game.relevance_of(node).priority
+    }
+
+    fn p0(&self, index: usize) -> NodeP0Id {
+        self.game.p0.incomplete[index]
+    }
+
+    fn p1(&self, index: usize) -> NodeP1Id {
+        self.game.p1.incomplete[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Candidates, ExpansionPolicy};
+    use crate::check::check;
+    use crate::index::IndexedVec;
+    use crate::local::LocalSolver;
+    use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+    use crate::symbolic::eq::{Expr, FixEq, FixType, VarId};
+    use crate::symbolic::formula::BasisElemId;
+    use crate::sync::Shared;
+
+    /// Expands short paths starting from the most relevant nodes.
+    struct RelevantFirst;
+
+    impl ExpansionPolicy for RelevantFirst {
+        fn explore_goal(&mut self, _: usize) -> usize {
+            1
+        }
+
+        fn select_start(&mut self, candidates: &Candidates<'_>) -> Option<usize> {
+            (0..candidates.len()).max_by_key(|&i| candidates.priority(i))
+        }
+
+        fn max_path_len(&self) -> Option<usize> {
+            Some(1)
+        }
+    }
+
+    /// Expands the oldest candidate first.
+    struct OldestFirst;
+
+    impl ExpansionPolicy for OldestFirst {
+        fn select_start(&mut self, candidates: &Candidates<'_>) -> Option<usize> {
+            (!candidates.is_empty()).then_some(0)
+        }
+    }
+
+    #[test]
+    fn custom_policies() {
+        // X0 = X1 && X2, X1 = X0 || X2, X2 = X1 && X0, X3 = X3, X4 = X3 || X1
+        let var = |i| Expr::Var(VarId(i));
+        let eqs = IndexedVec::from(vec![
+            FixEq { fix_type: FixType::Min, expr: Expr::And(vec![var(1), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(0), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::And(vec![var(1), var(0)]) },
+            FixEq { fix_type: FixType::Min, expr: var(3) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(3), var(1)]) },
+        ]);
+        let funs = Shared::new(FunsFormulas::new(IndexedVec::new()));
+        let formulas = Shared::new(EqsFormulas::new(eqs, funs));
+
+        let policies: [Box<dyn ExpansionPolicy>; 2] =
+            [Box::new(RelevantFirst), Box::new(OldestFirst)];
+        for policy in policies {
+            let mut solver = LocalSolver::with_policy(formulas.clone(), policy);
+            for i in (0..5).map(VarId) {
+                let b = BasisElemId(0);
+                let winner = LocalSolver::new(formulas.clone()).solve(b, i).winner();
+
+                let result = solver.solve(b, i);
+                assert_eq!(result.winner(), winner, "{i:?}");
+                let certificate = solver.certificate(result.init).unwrap();
+                assert_eq!(check(&formulas, result.init, &certificate), Ok(()), "{i:?}");
+            }
+        }
+    }
+}
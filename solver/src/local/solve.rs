@@ -1,93 +1,434 @@
-use std::rc::Rc;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
 
 use crate::index::IndexedVec;
 use crate::local::game::WinState;
-use crate::strategy::{improve, valuation, NodeId, PlayProfile};
-use crate::symbolic::compose::EqsFormulas;
+use crate::strategy::{
+    improve, update_valuation, valuation, NodeId, PlayProfile, Player, SwitchRule,
+};
+use crate::symbolic::compose::{CacheStats, EqsFormulas};
 use crate::symbolic::eq::VarId;
 use crate::symbolic::formula::BasisElemId;
 use crate::symbolic::moves::P0Pos;
+use crate::sync::Shared;
 
+use super::certificate::Certificate;
 use super::escape::update_winning_sets;
 use super::expansion::expand;
 use super::game::{Game, GameStrategy, NodeP0Id, NodeP1Id};
+use super::limits::{AbortReason, Aborted, Limits};
+use super::policy::{DefaultExpansion, ExpansionPolicy};
+use super::stats::SolveStats;
 
-pub fn solve(b: BasisElemId, i: VarId, moves: Rc<EqsFormulas>) -> bool {
-    let init = P0Pos { b, i };
+/// Result of solving the game for a given initial position. The strategy of the winning
+/// player can be obtained with [`LocalSolver::certificate`].
+#[derive(Clone, Debug)]
+pub struct SolveResult {
+    /// The position the game was solved for.
+    pub init: P0Pos,
+    winner: Player,
+    /// Statistics about the work done to solve `init`.
+    pub stats: SolveStats,
+}
+
+impl SolveResult {
+    pub fn winner(&self) -> Player {
+        self.winner
+    }
+
+    /// Whether player 0 wins, that is `init.b` is under the solution for `init.i`.
+    pub fn is_winning(&self) -> bool {
+        self.winner() == Player::P0
+    }
+}
+
+pub fn solve(b: BasisElemId, i: VarId, moves: Shared<EqsFormulas>) -> SolveResult {
+    LocalSolver::new(moves).solve(b, i)
+}
+
+pub fn solve_with_limits(
+    b: BasisElemId,
+    i: VarId,
+    moves: Shared<EqsFormulas>,
+    limits: &Limits,
+) -> Result<SolveResult, Aborted> {
+    LocalSolver::new(moves).solve_with_limits(b, i, limits)
+}
+
+/// Outcome of a single round of the local solver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// The game was expanded and the strategies improved, but the query is still undecided.
+    Progress,
+    /// The query was decided, with `true` meaning that player 0 wins.
+    Decided(bool),
+}
+
+/// Solver that keeps the explored game between queries, so that positions decided while
+/// answering a query don't need to be explored again for the following ones.
+///
+/// Queries can either be solved at once with [`LocalSolver::solve`], or started with
+/// [`LocalSolver::start`] and advanced one round at a time with [`LocalSolver::step`].
+pub struct LocalSolver {
+    game: Game,
+    strategy: GameStrategy,
+    profiles: IndexedVec<NodeId, PlayProfile>,
+    final_strategy: IndexedVec<NodeId, NodeId>,
+    policy: Box<dyn ExpansionPolicy>,
+    switch_rule: SwitchRule,
+    query: Option<Query>,
+}
 
-    // Special case to ensure there's always a move possible.
-    if init.moves(&moves).is_exhausted() {
-        return false;
+struct Query {
+    init: P0Pos,
+    // `None` if the position has no moves, and is thus trivially losing for player 0.
+    init_p0: Option<NodeP0Id>,
+    stats: SolveStats,
+    cache_before: CacheStats,
+}
+
+impl LocalSolver {
+    pub fn new(formulas: Shared<EqsFormulas>) -> Self {
+        Self::with_policy(formulas, Box::new(DefaultExpansion))
     }
 
-    let mut game = Game::new(init, moves);
-    let mut strategy = GameStrategy::new();
+    pub fn with_policy(formulas: Shared<EqsFormulas>, policy: Box<dyn ExpansionPolicy>) -> Self {
+        Self {
+            game: Game::new(formulas),
+            strategy: GameStrategy::new(),
+            profiles: initial_play_profiles(),
+            final_strategy: initial_final_strategy(),
+            policy,
+            switch_rule: SwitchRule::default(),
+            query: None,
+        }
+    }
 
-    // Dummy initial values
-    strategy.try_add(NodeP0Id::INIT, NodeP1Id::W1);
-    let mut profiles = initial_play_profiles();
-    let mut final_strategy = initial_final_strategy();
+    /// Sets the rule used to switch the strategy of player 0 when improving it.
+    pub fn set_switch_rule(&mut self, rule: SwitchRule) {
+        self.switch_rule = rule;
+    }
 
-    let mut expand_goal = 1;
+    /// The game explored so far.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Writes the game explored so far, together with the current strategies, in the Graphviz
+    /// DOT format. See [`Game::write_dot`].
+    pub fn write_dot(&self, w: &mut impl Write) -> io::Result<()> {
+        self.game.write_dot(w, &self.strategy, &self.final_strategy)
+    }
 
-    loop {
-        // Initially this will perform the initial expansion and set a proper successor for INIT.
+    pub fn solve(&mut self, b: BasisElemId, i: VarId) -> SolveResult {
+        match self.solve_with_limits(b, i, &Limits::default()) {
+            Ok(result) => result,
+            Err(_) => unreachable!("Aborted a query without limits"),
+        }
+    }
+
+    /// Solves the given position, unless `limits` are exceeded first. When aborted the game
+    /// explored so far is kept, so a later query can continue from where this one stopped.
+    pub fn solve_with_limits(
+        &mut self,
+        b: BasisElemId,
+        i: VarId,
+        limits: &Limits,
+    ) -> Result<SolveResult, Aborted> {
+        self.start(b, i);
+        loop {
+            match self.round(limits) {
+                Ok(Step::Progress) => {}
+                Ok(Step::Decided(_)) => return Ok(self.take_result().unwrap()),
+                Err(reason) => {
+                    let query = self.query.as_mut().unwrap();
+                    query.stats.record_game(&self.game, query.cache_before);
+                    return Err(Aborted { reason, stats: Box::new(query.stats.clone()) });
+                }
+            }
+        }
+    }
+
+    /// Starts a new query for the given position, replacing the current one if any.
+    pub fn start(&mut self, b: BasisElemId, i: VarId) {
+        let init = P0Pos { b, i };
+        let Self { game, strategy, profiles, final_strategy, .. } = self;
+        // Looking up the moves of a new initial position already counts towards the query.
+        let cache_before = game.formulas.cache_stats();
+
+        let init_p0 = match game.p0.pos.get_index_of(&init) {
+            Some(p0) => Some(p0),
+            // Special case to ensure there's always a move possible.
+            // Player 0 has no move, so player 1 wins without having to choose anything.
+            None if init.moves(&game.formulas).is_exhausted() => None,
+            None => {
+                // Dummy initial values, the first expansion will set a proper successor.
+                let p0 = game.insert_p0(init).id();
+                strategy.try_add(p0, NodeP1Id::W1);
+                profiles.push(PlayProfile {
+                    most_relevant: NodeId::W1,
+                    relevant_before: Vec::new(),
+                    count_before: 1,
+                });
+                final_strategy.push(NodeId::W1);
+                Some(p0)
+            }
+        };
+
+        self.query = Some(Query { init, init_p0, stats: SolveStats::default(), cache_before });
+    }
+
+    /// Performs a round of expansion and improvement for the current query, unless it is
+    /// already decided. Panics if no query was started.
+    pub fn step(&mut self) -> Step {
+        match self.round(&Limits::default()) {
+            Ok(step) => step,
+            Err(_) => unreachable!("Aborted a round without limits"),
+        }
+    }
+
+    /// Takes the result of the current query, if it was decided.
+    pub fn take_result(&mut self) -> Option<SolveResult> {
+        let winning = self.decided()?;
+
+        let Query { init, mut stats, cache_before, .. } = self.query.take().unwrap();
+        stats.record_game(&self.game, cache_before);
+        let winner = if winning { Player::P0 } else { Player::P1 };
+        Some(SolveResult { init, winner, stats })
+    }
+
+    /// Builds the strategy of the player winning on `pos`, or `None` if it was not decided by
+    /// the queries so far. This can be expensive, since player 1 must answer every move of
+    /// player 0, including the ones that were never explored.
+    pub fn certificate(&self, pos: P0Pos) -> Option<Certificate> {
+        match self.game.p0.pos.get_index_of(&pos) {
+            Some(p0) if self.game.p0.win[p0] == WinState::Unknown => None,
+            Some(p0) => Some(self.game.certificate(p0)),
+            // Positions without moves are never inserted, and player 1 wins them without
+            // having to choose anything.
+            None if pos.moves(&self.game.formulas).is_exhausted() => {
+                Some(Certificate::P1(Default::default()))
+            }
+            None => None,
+        }
+    }
+
+    /// Whether player 0 wins the current query, if it is decided.
+    fn decided(&self) -> Option<bool> {
+        let query = self.query.as_ref()?;
+        match query.init_p0.map(|p0| &self.game.p0.win[p0]) {
+            Some(WinState::Unknown) => None,
+            Some(WinState::Win0) => Some(true),
+            Some(WinState::Win1) | None => Some(false),
+        }
+    }
+
+    fn round(&mut self, limits: &Limits) -> Result<Step, AbortReason> {
+        if let Some(winning) = self.decided() {
+            return Ok(Step::Decided(winning));
+        }
+
+        let Self { game, strategy, profiles, final_strategy, policy, query, .. } = self;
+        let query = query.as_mut().expect("No query started");
+        let stats = &mut query.stats;
+        let init_id = game.p0.ids[query.init_p0.unwrap()];
+
+        // Initially this will perform the initial expansion and set a proper successor for init.
         // Later on it will expand the graph, potentially running `update_winning_sets`.
-        let solved =
-            expand(&mut game, &mut profiles, &mut final_strategy, &mut strategy, expand_goal);
-        expand_goal *= 2;
+        let expand_goal = policy.explore_goal(stats.expansions);
+        let solved = timed(&mut stats.expansion_time, || {
+            let policy = &mut **policy;
+            expand(game, profiles, final_strategy, strategy, init_id, expand_goal, limits, policy)
+        });
+        stats.expansions += 1;
 
+        // When the limits stop the expansion early the strategy may already have been improved,
+        // so the query is only aborted after the valuation below brings the profiles up to date.
         // If expansion finished the successors and no improvement happened then see who won.
         if solved {
-            update_winning_sets(&mut game, &profiles, &mut final_strategy, &mut strategy);
-            match game.p0.win[NodeP0Id::INIT] {
-                WinState::Win0 => return true,
-                WinState::Win1 => return false,
-                WinState::Unknown => {}
+            timed(&mut stats.update_time, || {
+                update_winning_sets(game, profiles, final_strategy, strategy)
+            });
+            if let Some(winning) = self.decided() {
+                return Ok(Step::Decided(winning));
             }
         }
 
-        // Try to improve while possible
-        let mut improved = true;
-        while improved {
-            (profiles, final_strategy) = valuation(&game, &strategy);
-            improved = improve(&game, &mut strategy, &profiles);
+        let Self { game, strategy, profiles, final_strategy, switch_rule, query, .. } = self;
+        let stats = &mut query.as_mut().unwrap().stats;
+
+        // Try to improve while possible. After the first valuation only the nodes affected by
+        // the switched nodes need to be evaluated again.
+        let mut switched = None;
+        loop {
+            timed(&mut stats.valuation_time, || match switched.take() {
+                None => (*profiles, *final_strategy) = valuation(game, strategy),
+                Some(switched) => {
+                    stats.revaluated_nodes +=
+                        update_valuation(game, strategy, switched, profiles, final_strategy);
+                }
+            });
+            stats.valuations += 1;
+
+            // Abort only when the profiles are up to date with the strategy.
+            if let Some(reason) = limits.exceeded(game) {
+                return Err(reason);
+            }
+
+            let nodes = timed(&mut stats.improvement_time, || {
+                improve(game, strategy, profiles, switch_rule)
+            });
+            stats.improvements += 1;
+
+            if nodes.is_empty() {
+                break;
+            }
+            switched = Some(nodes);
         }
 
         // Update definitely winning/losing nodes.
-        update_winning_sets(&mut game, &profiles, &mut final_strategy, &mut strategy);
-
-        // println!(
-        //     "p0: {}, p1: {}, edges: {}, w0: {}, w1: {}, took: {:?}",
-        //     game.p0.ids.len(),
-        //     game.p1.ids.len(),
-        //     game.p0.succs.iter().flatten().count() + game.p1.succs.iter().flatten().count(),
-        //     game.p0.w0.len() + game.p1.w0.len(),
-        //     game.p0.w1.len() + game.p1.w1.len(),
-        //     now.elapsed()
-        // );
-
-        // Check if the initial node is definitely winning/losing after the update.
-        match game.p0.win[NodeP0Id::INIT] {
-            WinState::Win0 => return true,
-            WinState::Win1 => return false,
-            WinState::Unknown => {}
-        }
+        timed(&mut stats.update_time, || {
+            update_winning_sets(game, profiles, final_strategy, strategy)
+        });
+
+        Ok(match self.decided() {
+            Some(winning) => Step::Decided(winning),
+            None => Step::Progress,
+        })
+    }
+}
+
+fn timed<T>(time: &mut Duration, f: impl FnOnce() -> T) -> T {
+    let now = Instant::now();
+    let result = f();
+    *time += now.elapsed();
+    result
+}
+
+impl SolveStats {
+    fn record_game(&mut self, game: &Game, cache_before: CacheStats) {
+        self.p0_nodes = game.p0.ids.len();
+        self.p1_nodes = game.p1.ids.len();
+        self.edges = game.explored_edges;
+        self.w0_nodes = game.p0.w0.len() + game.p1.w0.len();
+        self.w1_nodes = game.p0.w1.len() + game.p1.w1.len();
+
+        let cache = game.formulas.cache_stats();
+        self.cache = CacheStats {
+            hits: cache.hits - cache_before.hits,
+            misses: cache.misses - cache_before.misses,
+        };
+        self.formula_nodes = game.formulas.formula_nodes();
     }
 }
 
 fn initial_play_profiles() -> IndexedVec<NodeId, PlayProfile> {
-    // Corresponding nodes are: W0, L0, W1, L1, INIT
+    // Corresponding nodes are: W0, L0, W1, L1
     IndexedVec::from(vec![
         PlayProfile { most_relevant: NodeId::L1, relevant_before: Vec::new(), count_before: 1 },
         PlayProfile { most_relevant: NodeId::W1, relevant_before: Vec::new(), count_before: 1 },
         PlayProfile { most_relevant: NodeId::W1, relevant_before: Vec::new(), count_before: 0 },
         PlayProfile { most_relevant: NodeId::L1, relevant_before: Vec::new(), count_before: 0 },
-        PlayProfile { most_relevant: NodeId::W1, relevant_before: Vec::new(), count_before: 1 },
     ])
 }
 
 fn initial_final_strategy() -> IndexedVec<NodeId, NodeId> {
-    // Corresponding nodes are: W0, L0, W1, L1, INIT
-    IndexedVec::from(vec![NodeId::L1, NodeId::W1, NodeId::L0, NodeId::W0, NodeId::W1])
+    // Corresponding nodes are: W0, L0, W1, L1
+    IndexedVec::from(vec![NodeId::L1, NodeId::W1, NodeId::L0, NodeId::W0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LocalSolver, Step};
+    use crate::index::IndexedVec;
+    use crate::strategy::Player;
+    use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+    use crate::symbolic::eq::{Expr, FixEq, FixType, VarId};
+    use crate::symbolic::formula::BasisElemId;
+    use crate::sync::Shared;
+
+    fn formulas() -> Shared<EqsFormulas> {
+        // X0 = X1 && X2, X1 = X0 || X2, X2 = X1 && X0, X3 = X3, X4 = X3 || X1
+        let var = |i| Expr::Var(VarId(i));
+        let eqs = IndexedVec::from(vec![
+            FixEq { fix_type: FixType::Min, expr: Expr::And(vec![var(1), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(0), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::And(vec![var(1), var(0)]) },
+            FixEq { fix_type: FixType::Min, expr: var(3) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(3), var(1)]) },
+        ]);
+        let funs = Shared::new(FunsFormulas::new(IndexedVec::new()));
+        Shared::new(EqsFormulas::new(eqs, funs))
+    }
+
+    #[test]
+    fn reuses_explored_game() {
+        let formulas = formulas();
+        let b = BasisElemId(0);
+
+        let mut solver = LocalSolver::new(formulas.clone());
+        for i in (0..5).map(VarId) {
+            let winner = LocalSolver::new(formulas.clone()).solve(b, i).winner();
+            assert_eq!(solver.solve(b, i).winner(), winner, "{i:?}");
+
+            // Asking again is answered by the game explored so far.
+            let nodes = solver.game().nodes.len();
+            let result = solver.solve(b, i);
+            assert_eq!(result.winner(), winner, "{i:?}");
+            assert_eq!(result.stats.expansions, 0, "{i:?}");
+            assert_eq!(solver.game().nodes.len(), nodes, "{i:?}");
+        }
+    }
+
+    #[test]
+    fn steps_match_solve() {
+        let formulas = formulas();
+        let b = BasisElemId(0);
+
+        let mut solver = LocalSolver::new(formulas.clone());
+        for i in (0..5).map(VarId) {
+            let winner = LocalSolver::new(formulas.clone()).solve(b, i).winner();
+
+            solver.start(b, i);
+            let mut step = solver.step();
+            while step == Step::Progress {
+                step = solver.step();
+            }
+            assert_eq!(step, Step::Decided(winner == Player::P0), "{i:?}");
+            // Once decided, further steps keep reporting the same outcome.
+            assert_eq!(solver.step(), step, "{i:?}");
+
+            let result = solver.take_result().unwrap();
+            assert_eq!(result.winner(), winner, "{i:?}");
+            assert!(solver.take_result().is_none());
+        }
+    }
+
+    #[test]
+    fn records_stats() {
+        // Expansions, improvements, valuations, p0 and p1 nodes, cache hits and misses.
+        // X1 and X2 are decided while solving X0, so they are answered without any work.
+        let expected = [
+            (2, 1, 1, 3, 3, 1, 3),
+            (0, 0, 0, 3, 3, 0, 0),
+            (0, 0, 0, 3, 3, 0, 0),
+            (2, 1, 1, 4, 4, 1, 1),
+            (1, 1, 1, 5, 5, 1, 1),
+        ];
+
+        let mut solver = LocalSolver::new(formulas());
+        for (i, expected) in (0..5).map(VarId).zip(expected) {
+            let stats = solver.solve(BasisElemId(0), i).stats;
+            let counters = (
+                stats.expansions,
+                stats.improvements,
+                stats.valuations,
+                stats.p0_nodes,
+                stats.p1_nodes,
+                stats.cache.hits,
+                stats.cache.misses,
+            );
+            assert_eq!(counters, expected, "{i:?}");
+        }
+    }
 }
@@ -0,0 +1,57 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::symbolic::compose::CacheStats;
+
+/// Statistics about a query to the local solver.
+///
+/// Counters and timings only refer to the query, while the sizes refer to the whole game
+/// explored so far, which could have been partially built by previous queries.
+#[derive(Clone, Debug, Default)]
+pub struct SolveStats {
+    /// Number of expansion phases.
+    pub expansions: usize,
+    /// Number of strategy improvement iterations.
+    pub improvements: usize,
+    /// Number of valuations computed.
+    pub valuations: usize,
+    /// Number of nodes evaluated again by the incremental valuations after an improvement.
+    pub revaluated_nodes: usize,
+    /// Number of player 0 nodes in the game.
+    pub p0_nodes: usize,
+    /// Number of player 1 nodes in the game.
+    pub p1_nodes: usize,
+    /// Number of edges explored in the game.
+    pub edges: usize,
+    /// Number of nodes known to be winning for player 0.
+    pub w0_nodes: usize,
+    /// Number of nodes known to be winning for player 1.
+    pub w1_nodes: usize,
+    pub expansion_time: Duration,
+    pub valuation_time: Duration,
+    pub improvement_time: Duration,
+    /// Time spent updating the sets of winning nodes.
+    pub update_time: Duration,
+    /// Lookups in the formulas caches.
+    pub cache: CacheStats,
+    /// Number of distinct nodes in the formulas of the positions.
+    pub formula_nodes: usize,
+}
+
+impl fmt::Display for SolveStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Expansions: {}", self.expansions)?;
+        writeln!(f, "Improvements: {}", self.improvements)?;
+        writeln!(f, "Valuations: {}", self.valuations)?;
+        writeln!(f, "Revaluated nodes: {}", self.revaluated_nodes)?;
+        writeln!(f, "Nodes: {} p0, {} p1", self.p0_nodes, self.p1_nodes)?;
+        writeln!(f, "Edges: {}", self.edges)?;
+        writeln!(f, "Decided nodes: {} w0, {} w1", self.w0_nodes, self.w1_nodes)?;
+        writeln!(f, "Expansion took {:?}", self.expansion_time)?;
+        writeln!(f, "Valuation took {:?}", self.valuation_time)?;
+        writeln!(f, "Improvement took {:?}", self.improvement_time)?;
+        writeln!(f, "Update of winning sets took {:?}", self.update_time)?;
+        writeln!(f, "Formula cache: {} hits, {} misses", self.cache.hits, self.cache.misses)?;
+        write!(f, "Formula nodes: {}", self.formula_nodes)
+    }
+}
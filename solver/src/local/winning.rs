@@ -25,7 +25,7 @@ impl Game {
         // Optimization: remove edges from predecessors and set them as winning.
         for p1 in std::mem::take(&mut self.p0.preds[p0]) {
             if self.p1.win[p1] != WinState::Win1 {
-                self.set_p1_winning(p1, strategy, final_strategy);
+                self.set_p1_winning(p1, p0, strategy, final_strategy);
             }
         }
     }
@@ -33,11 +33,13 @@ impl Game {
     pub fn set_p0_winning(
         &mut self,
         p0: NodeP0Id,
+        mov: NodeP1Id,
         strategy: &mut GameStrategy,
         final_strategy: &mut IndexedVec<NodeId, NodeId>,
     ) {
         self.p0.win[p0] = WinState::Win0;
         self.p0.w0.insert(p0);
+        self.p0.win_move.insert(p0, mov);
         self.p0.incomplete.swap_remove(&p0);
 
         // Fixup strategy.
@@ -85,7 +87,7 @@ impl Game {
         // Optimization: remove edges from predecessors and set them as winning.
         for p0 in std::mem::take(&mut self.p1.preds[p1]) {
             if self.p0.win[p0] != WinState::Win0 {
-                self.set_p0_winning(p0, strategy, final_strategy);
+                self.set_p0_winning(p0, p1, strategy, final_strategy);
             }
         }
     }
@@ -93,11 +95,13 @@ impl Game {
     pub fn set_p1_winning(
         &mut self,
         p1: NodeP1Id,
+        mov: NodeP0Id,
         strategy: &mut GameStrategy,
         final_strategy: &mut IndexedVec<NodeId, NodeId>,
     ) {
         self.p1.win[p1] = WinState::Win1;
         self.p1.w1.insert(p1);
+        self.p1.win_move.insert(p1, mov);
         self.p1.incomplete.swap_remove(&p1);
 
         // Fixup strategy.
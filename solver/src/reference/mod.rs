@@ -0,0 +1,86 @@
+//! Classic global solvers for explicit parity games, useful as baselines and as oracles for
+//! the strategy iteration solvers.
+
+mod promotion;
+mod zielonka;
+
+pub use promotion::priority_promotion;
+pub use zielonka::zielonka;
+
+use crate::index::IndexedVec;
+use crate::strategy::{NodeId, NodeMap, ParityGraph, Player, Set};
+
+fn priority_of(graph: &impl ParityGraph, n: NodeId) -> usize {
+    graph.relevance_of(n).priority
+}
+
+// The player favoured by a priority.
+fn player_of_priority(priority: usize) -> Player {
+    match priority % 2 {
+        0 => Player::P0,
+        _ => Player::P1,
+    }
+}
+
+// Initially each node moves to its first successor.
+fn initial_strategy(graph: &impl ParityGraph) -> IndexedVec<NodeId, NodeId> {
+    (0..graph.node_count())
+        .map(|n| graph.successors_of(NodeId(n)).next().expect("Node without successors"))
+        .collect()
+}
+
+/// Computes the nodes of the subgame made of `nodes` from which `player` can force the play
+/// to reach `target`, recording the moves that do so in `strategy`. The result includes `target`.
+fn attractor(
+    graph: &impl ParityGraph,
+    nodes: &Set<NodeId>,
+    target: impl IntoIterator<Item = NodeId>,
+    player: Player,
+    strategy: &mut IndexedVec<NodeId, NodeId>,
+) -> Set<NodeId> {
+    let mut attr = target.into_iter().collect::<Set<_>>();
+    let mut stack = attr.iter().copied().collect::<Vec<_>>();
+    let mut remaining = NodeMap::default();
+
+    while let Some(v) = stack.pop() {
+        for u in graph.predecessors_of(v) {
+            if !nodes.contains(&u) || attr.contains(&u) {
+                continue;
+            }
+
+            if graph.player_of(u) == player {
+                strategy[u] = v;
+            } else {
+                // The opponent is attracted only once all its moves are.
+                let count = remaining.entry(u).or_insert_with(|| {
+                    graph.successors_of(u).filter(|w| nodes.contains(w)).count()
+                });
+                *count -= 1;
+                if *count > 0 {
+                    continue;
+                }
+            }
+
+            attr.insert(u);
+            stack.push(u);
+        }
+    }
+
+    attr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{priority_promotion, zielonka};
+    use crate::strategy::test::test_solver;
+
+    #[test]
+    fn zielonka_solves_tests() {
+        test_solver(zielonka);
+    }
+
+    #[test]
+    fn priority_promotion_solves_tests() {
+        test_solver(priority_promotion);
+    }
+}
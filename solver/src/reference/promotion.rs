@@ -0,0 +1,94 @@
+use crate::index::IndexedVec;
+use crate::strategy::{NodeId, NodeMap, ParityGraph, ParitySolution, Player, Set};
+
+use super::{attractor, initial_strategy, player_of_priority, priority_of};
+
+/// Solves a parity game with the priority promotion algorithm.
+///
+/// Every node must have at least one successor.
+pub fn priority_promotion<G: ParityGraph>(graph: &G) -> ParitySolution {
+    let mut nodes = (0..graph.node_count()).map(NodeId).collect::<Set<_>>();
+    let mut strategy = initial_strategy(graph);
+    let mut winner = IndexedVec::from(vec![Player::P0; graph.node_count()]);
+
+    // Repeatedly find a dominion and remove everything its winner can attract to it.
+    while !nodes.is_empty() {
+        let (dominion, player) = search_dominion(graph, &nodes, &mut strategy);
+        let won = attractor(graph, &nodes, dominion, player, &mut strategy);
+        for &n in &won {
+            winner[n] = player;
+        }
+        nodes.retain(|n| !won.contains(n));
+    }
+
+    ParitySolution { winner, strategy }
+}
+
+// Finds a dominion in the subgame made of `nodes`, returning it together with its winner.
+fn search_dominion(
+    graph: &impl ParityGraph,
+    nodes: &Set<NodeId>,
+    strategy: &mut IndexedVec<NodeId, NodeId>,
+) -> (Set<NodeId>, Player) {
+    // The priority of the region each node is in, initially the one of the node itself.
+    let mut region = nodes.iter().map(|&n| (n, priority_of(graph, n))).collect::<NodeMap<_>>();
+    let mut p = region.values().copied().max().unwrap();
+
+    loop {
+        let player = player_of_priority(p);
+
+        // Compute the region with priority p in the subgame without the higher regions.
+        let sub = nodes.iter().copied().filter(|n| region[n] <= p).collect::<Set<_>>();
+        let top = sub.iter().copied().filter(|n| region[n] == p);
+        let attr = attractor(graph, &sub, top, player, strategy);
+        for &n in &attr {
+            region.insert(n, p);
+        }
+
+        // The region is closed if player can stay in it and the opponent can't escape
+        // to lower regions. The moves of promoted nodes already stay in the region.
+        let mut closed = true;
+        for &n in &attr {
+            if graph.player_of(n) == player {
+                if !attr.contains(&strategy[n]) {
+                    match graph.successors_of(n).find(|m| attr.contains(m)) {
+                        Some(m) => strategy[n] = m,
+                        None => closed = false,
+                    }
+                }
+            } else if graph.successors_of(n).any(|m| sub.contains(&m) && !attr.contains(&m)) {
+                closed = false;
+            }
+        }
+
+        if !closed {
+            // Continue with the next lower region, which is not empty since the subgame
+            // is total and the region isn't closed in it.
+            let lower = sub.iter().filter(|&n| !attr.contains(n)).map(|n| region[n]).max();
+            p = lower.unwrap();
+            continue;
+        }
+
+        // The opponent can only escape to higher regions, find the lowest one.
+        let escape = attr
+            .iter()
+            .filter(|&&n| graph.player_of(n) != player)
+            .flat_map(|&n| graph.successors_of(n))
+            .filter(|m| nodes.contains(m) && !attr.contains(m))
+            .map(|m| region[&m])
+            .min();
+
+        // If the opponent can't escape at all then this is a dominion.
+        let Some(q) = escape else { return (attr, player) };
+
+        // Otherwise merge the region into the one it escapes to, and reset the lower ones.
+        for (n, r) in region.iter_mut() {
+            if attr.contains(n) {
+                *r = q;
+            } else if *r < q {
+                *r = priority_of(graph, *n);
+            }
+        }
+        p = q;
+    }
+}
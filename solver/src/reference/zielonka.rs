@@ -0,0 +1,59 @@
+use crate::index::IndexedVec;
+use crate::strategy::{NodeId, ParityGraph, ParitySolution, Player, Set};
+
+use super::{attractor, initial_strategy, player_of_priority, priority_of};
+
+/// Solves a parity game with Zielonka's recursive algorithm.
+///
+/// Every node must have at least one successor.
+pub fn zielonka<G: ParityGraph>(graph: &G) -> ParitySolution {
+    let nodes = (0..graph.node_count()).map(NodeId).collect();
+    let mut strategy = initial_strategy(graph);
+
+    let [w0, _] = solve(graph, nodes, &mut strategy);
+
+    let winner = (0..graph.node_count())
+        .map(|n| if w0.contains(&NodeId(n)) { Player::P0 } else { Player::P1 })
+        .collect();
+    ParitySolution { winner, strategy }
+}
+
+// Returns the winning regions of player 0 and player 1 in the subgame made of `nodes`,
+// setting the strategy of the nodes each player wins.
+fn solve(
+    graph: &impl ParityGraph,
+    mut nodes: Set<NodeId>,
+    strategy: &mut IndexedVec<NodeId, NodeId>,
+) -> [Set<NodeId>; 2] {
+    let mut won = [Set::default(), Set::default()];
+
+    // Each iteration removes the region won by the opponent of the player favoured by the
+    // highest priority, and solves again the rest, until the opponent wins nothing.
+    while let Some(max) = nodes.iter().map(|&n| priority_of(graph, n)).max() {
+        let player = player_of_priority(max);
+        let opponent = player.opponent();
+
+        // Nodes with the highest priority just need to stay in the subgame.
+        let top = nodes.iter().copied().filter(|&n| priority_of(graph, n) == max);
+        let top = top.collect::<Vec<_>>();
+        for &n in top.iter().filter(|&&n| graph.player_of(n) == player) {
+            strategy[n] = graph.successors_of(n).find(|m| nodes.contains(m)).unwrap();
+        }
+
+        let attr = attractor(graph, &nodes, top, player, strategy);
+        let rest = nodes.iter().copied().filter(|n| !attr.contains(n)).collect();
+        let sub = solve(graph, rest, strategy);
+
+        if sub[opponent as usize].is_empty() {
+            won[player as usize].extend(nodes);
+            break;
+        }
+
+        let lost =
+            attractor(graph, &nodes, sub[opponent as usize].iter().copied(), opponent, strategy);
+        nodes.retain(|n| !lost.contains(n));
+        won[opponent as usize].extend(lost);
+    }
+
+    won
+}
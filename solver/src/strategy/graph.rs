@@ -21,6 +21,15 @@ pub enum Player {
     P1,
 }
 
+impl Player {
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::P0 => Player::P1,
+            Player::P1 => Player::P0,
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Relevance {
     // Actual priority
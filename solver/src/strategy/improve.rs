@@ -2,26 +2,206 @@ use crate::index::IndexedVec;
 
 use super::{NodeId, ParityGraph, PlayProfile, StrategyMut};
 
+/// Rule deciding which nodes switch successor during an improvement step, and to which one.
+/// Any of them switches only to successors with a better play profile, so they all lead to
+/// the same optimal strategy, possibly with a different number of valuations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SwitchRule {
+    /// Switch every improvable node to its best successor.
+    #[default]
+    Best,
+    /// Switch every improvable node to the first successor better than the current one.
+    Greedy,
+    /// Switch only the first improvable node, to its best successor.
+    Single,
+    /// Switch every improvable node to a random successor better than the current one.
+    /// The state of the generator is updated as the rule is used.
+    Random(u64),
+}
+
+impl SwitchRule {
+    /// Randomised rule starting from the given seed.
+    pub fn random(seed: u64) -> Self {
+        // Scramble the seed with splitmix64, so that close seeds give unrelated sequences.
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        // Avoid the zero state, which xorshift never leaves.
+        Self::Random(if z == 0 { 0x9e37_79b9_7f4a_7c15 } else { z })
+    }
+
+    // xorshift64*, good enough to pick successors.
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state >> 12;
+        *state ^= *state << 25;
+        *state ^= *state >> 27;
+        state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+/// Switches the strategy according to `rule`, returning the nodes whose successor changed.
 pub fn improve<S: StrategyMut>(
     graph: &S::Graph,
     strategy: &mut S,
     profiles: &IndexedVec<NodeId, PlayProfile>,
-) -> bool {
-    let mut improved = false;
+    rule: &mut SwitchRule,
+) -> Vec<NodeId> {
+    let mut switched = Vec::new();
+    let is_better = |n0, n1, n2| PlayProfile::compare(graph, profiles, n0, n1, n2).is_lt();
 
     // For each node in the strategy try to improve it.
-    strategy.update_each(graph, |n0, mut n1| {
-        // For each successor check if its play profile is better
-        for n2 in graph.successors_of(n0) {
-            // Compare the profiles of n1 and n2
-            if PlayProfile::compare(graph, profiles, n0, n1, n2).is_lt() {
-                // If it's better update the strategy
-                n1 = n2;
-                improved = true;
+    strategy.update_each(graph, |n0, n1| {
+        let mut next = n1;
+        match rule {
+            SwitchRule::Single if !switched.is_empty() => return n1,
+            SwitchRule::Best | SwitchRule::Single => {
+                // Keep the best successor seen so far.
+                for n2 in graph.successors_of(n0) {
+                    if is_better(n0, next, n2) {
+                        next = n2;
+                    }
+                }
+            }
+            SwitchRule::Greedy => {
+                next = graph.successors_of(n0).find(|&n2| is_better(n0, n1, n2)).unwrap_or(n1);
+            }
+            SwitchRule::Random(state) => {
+                // Reservoir sampling among the successors better than the current one.
+                let mut seen = 0;
+                for n2 in graph.successors_of(n0).filter(|&n2| is_better(n0, n1, n2)) {
+                    seen += 1;
+                    if SwitchRule::next_random(state).is_multiple_of(seen) {
+                        next = n2;
+                    }
+                }
             }
         }
-        n1
+
+        if next != n1 {
+            switched.push(n0);
+        }
+        next
     });
 
-    improved
+    switched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{improve, SwitchRule};
+    use crate::index::IndexedVec;
+    use crate::local::LocalSolver;
+    use crate::strategy::solve::ExplicitStrategy;
+    use crate::strategy::test::{parse_test, TestGame};
+    use crate::strategy::{
+        update_valuation, valuation, NodeId, ParityGraph, PlayProfile, Player, Strategy,
+    };
+    use crate::symbolic::compose::{EqsFormulas, FunsFormulas};
+    use crate::symbolic::eq::{Expr, FixEq, FixType, VarId};
+    use crate::symbolic::formula::BasisElemId;
+    use crate::sync::Shared;
+    use crate::Set;
+
+    /// Runs strategy iteration on `game` with `rule`, checking that each improvement switches
+    /// only improvable nodes and returning the nodes switched by each of them.
+    fn run_improvements(game: &TestGame, mut rule: SwitchRule) -> Vec<Vec<NodeId>> {
+        let mut strategy = ExplicitStrategy::new(game);
+        let (mut profiles, mut final_strategy) = valuation(game, &strategy);
+
+        let mut rounds = Vec::new();
+        loop {
+            let improvable = (0..game.node_count())
+                .map(NodeId)
+                .filter(|&n| game.player_of(n) == Player::P0)
+                .filter(|&n| {
+                    let current = strategy.get_direct(n, game);
+                    let mut succs = game.successors_of(n);
+                    succs.any(|m| PlayProfile::compare(game, &profiles, n, current, m).is_lt())
+                })
+                .collect::<Set<_>>();
+
+            let switched = improve(game, &mut strategy, &profiles, &mut rule);
+            let unique = switched.iter().copied().collect::<Set<_>>();
+            assert_eq!(unique.len(), switched.len(), "{rule:?} switched a node twice");
+            assert!(unique.is_subset(&improvable), "{rule:?} switched a node not improvable");
+            match rule {
+                SwitchRule::Single => assert_eq!(switched.len(), improvable.len().min(1)),
+                _ => assert_eq!(unique, improvable, "{rule:?} left improvable nodes"),
+            }
+
+            if switched.is_empty() {
+                return rounds;
+            }
+            update_valuation(game, &strategy, switched.clone(), &mut profiles, &mut final_strategy);
+            rounds.push(switched);
+        }
+    }
+
+    #[test]
+    fn switched_nodes() {
+        // Player 0 starts with the first successors, so 0, 2 and 4 can all improve.
+        let game = parse_test(
+            "parity 6;
+0 1 0 1,3;
+1 3 1 0;
+2 1 0 1,5;
+3 4 1 3;
+4 1 0 1,3;
+5 6 1 5;",
+        );
+
+        let single = run_improvements(&game, SwitchRule::Single);
+        let greedy = run_improvements(&game, SwitchRule::Greedy);
+        assert_eq!(greedy[0].len(), 3);
+        assert!(single.len() > greedy.len());
+
+        // The same seed always makes the same choices.
+        let random = run_improvements(&game, SwitchRule::random(42));
+        assert_eq!(run_improvements(&game, SwitchRule::random(42)), random);
+    }
+
+    #[test]
+    fn random_seeds_diverge() {
+        let sequence = |seed| {
+            let SwitchRule::Random(mut state) = SwitchRule::random(seed) else {
+                unreachable!()
+            };
+            (0..4).map(|_| SwitchRule::next_random(&mut state)).collect::<Vec<_>>()
+        };
+
+        let sequences = (0..64).map(sequence).collect::<Vec<_>>();
+        for (i, a) in sequences.iter().enumerate() {
+            for (j, b) in sequences.iter().enumerate().skip(i + 1) {
+                assert_ne!(a[0], b[0], "Seeds {i} and {j} start the same");
+                assert_ne!(a, b, "Seeds {i} and {j} give the same sequence");
+            }
+        }
+    }
+
+    #[test]
+    fn rules_agree() {
+        // X0 = X1 && X2, X1 = X0 || X2, X2 = X1 && X0, X3 = X3, X4 = X3 || X1
+        let var = |i| Expr::Var(VarId(i));
+        let eqs = IndexedVec::from(vec![
+            FixEq { fix_type: FixType::Min, expr: Expr::And(vec![var(1), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(0), var(2)]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::And(vec![var(1), var(0)]) },
+            FixEq { fix_type: FixType::Min, expr: var(3) },
+            FixEq { fix_type: FixType::Max, expr: Expr::Or(vec![var(3), var(1)]) },
+        ]);
+        let funs = Shared::new(FunsFormulas::new(IndexedVec::new()));
+        let formulas = Shared::new(EqsFormulas::new(eqs, funs));
+
+        let rules = [SwitchRule::Greedy, SwitchRule::Single, SwitchRule::random(42)];
+        for rule in rules {
+            let mut solver = LocalSolver::new(formulas.clone());
+            solver.set_switch_rule(rule);
+            for i in (0..5).map(VarId) {
+                let b = BasisElemId(0);
+                let winner = LocalSolver::new(formulas.clone()).solve(b, i).winner();
+                assert_eq!(solver.solve(b, i).winner(), winner, "{rule:?} {i:?}");
+            }
+        }
+    }
 }
@@ -3,18 +3,20 @@
 mod graph;
 mod improve;
 mod profile;
+mod solve;
 mod valuation;
 
 #[cfg(test)]
-mod test;
+pub(crate) mod test;
 
 use std::iter;
 
 use either::Either::*;
 pub use graph::{GetRelevance, NodeId, ParityGraph, Player, Relevance, Reward};
-pub use improve::improve;
+pub use improve::{improve, SwitchRule};
 pub use profile::PlayProfile;
-pub use valuation::valuation;
+pub use solve::{solve_parity_game, ParitySolution};
+pub use valuation::{update_valuation, valuation};
 
 pub type Set<T> = indexmap::IndexSet<T, rustc_hash::FxBuildHasher>;
 pub type NodeMap<T> = rustc_hash::FxHashMap<NodeId, T>;
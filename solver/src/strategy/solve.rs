@@ -0,0 +1,111 @@
+use std::marker::PhantomData;
+
+use crate::index::IndexedVec;
+
+use super::{
+    improve, update_valuation, valuation, NodeId, ParityGraph, Player, Set, Strategy, StrategyMut,
+    SwitchRule,
+};
+
+/// Winning regions and strategies of both players in a parity game.
+#[derive(Clone)]
+pub struct ParitySolution {
+    /// Winner of each node.
+    pub winner: IndexedVec<NodeId, Player>,
+    /// Successor chosen for each node by the player controlling it. Following it is winning
+    /// for the player controlling the node whenever that player is also its winner.
+    pub strategy: IndexedVec<NodeId, NodeId>,
+}
+
+impl ParitySolution {
+    /// The nodes won by `player`.
+    pub fn winning_region(&self, player: Player) -> impl Iterator<Item = NodeId> + '_ {
+        self.winner.enumerate().filter(move |&(_, &p)| p == player).map(|(n, _)| n)
+    }
+
+    /// The moves of `player` on the nodes it wins and controls.
+    pub fn winning_strategy<'a>(
+        &'a self,
+        graph: &'a impl ParityGraph,
+        player: Player,
+    ) -> impl Iterator<Item = (NodeId, NodeId)> + 'a {
+        self.winning_region(player)
+            .filter(move |&n| graph.player_of(n) == player)
+            .map(|n| (n, self.strategy[n]))
+    }
+}
+
+/// Solves a parity game with strategy iteration over the whole graph.
+///
+/// Every node must have at least one successor.
+pub fn solve_parity_game<G: ParityGraph>(graph: &G) -> ParitySolution {
+    let mut strategy = ExplicitStrategy::<G>::new(graph);
+
+    let (mut profiles, mut final_strategy) = valuation(graph, &strategy);
+    loop {
+        let switched = improve(graph, &mut strategy, &profiles, &mut SwitchRule::default());
+        if switched.is_empty() {
+            break;
+        }
+        update_valuation(graph, &strategy, switched, &mut profiles, &mut final_strategy);
+    }
+
+    let winner = profiles.iter().map(|profile| profile.winning(graph)).collect();
+    ParitySolution { winner, strategy: final_strategy }
+}
+
+/// Strategy for player 0 on an arbitrary graph.
+pub(super) struct ExplicitStrategy<G> {
+    direct: IndexedVec<NodeId, NodeId>,
+    inverse: IndexedVec<NodeId, Set<NodeId>>,
+    _graph: PhantomData<fn(&G)>,
+}
+
+impl<G: ParityGraph> ExplicitStrategy<G> {
+    // Initially pick the first successor of each player 0 node.
+    pub(super) fn new(graph: &G) -> Self {
+        let nodes = (0..graph.node_count()).map(NodeId);
+        let mut direct = IndexedVec::from(vec![NodeId(usize::MAX); graph.node_count()]);
+        let mut inverse = IndexedVec::from(vec![Set::default(); graph.node_count()]);
+        for n in nodes.filter(|&n| graph.player_of(n) == Player::P0) {
+            let next = graph.successors_of(n).next().expect("Node without successors");
+            direct[n] = next;
+            inverse[next].insert(n);
+        }
+        Self { direct, inverse, _graph: PhantomData }
+    }
+}
+
+impl<G: ParityGraph> Strategy for ExplicitStrategy<G> {
+    type Graph = G;
+
+    fn iter(&self, graph: &G) -> impl Iterator<Item = (NodeId, NodeId)> {
+        let nodes = self.direct.enumerate().filter(|&(n, _)| graph.player_of(n) == Player::P0);
+        nodes.map(|(n, &m)| (n, m))
+    }
+
+    fn get_direct(&self, n: NodeId, _: &G) -> NodeId {
+        self.direct[n]
+    }
+
+    fn get_inverse(&self, n: NodeId, _: &G) -> impl Iterator<Item = NodeId> {
+        self.inverse[n].iter().copied()
+    }
+}
+
+impl<G: ParityGraph> StrategyMut for ExplicitStrategy<G> {
+    fn update_each(&mut self, graph: &G, mut f: impl FnMut(NodeId, NodeId) -> NodeId) {
+        for n in (0..graph.node_count()).map(NodeId) {
+            if graph.player_of(n) != Player::P0 {
+                continue;
+            }
+
+            let (prev, next) = (self.direct[n], f(n, self.direct[n]));
+            if next != prev {
+                self.inverse[prev].swap_remove(&n);
+                self.inverse[next].insert(n);
+                self.direct[n] = next;
+            }
+        }
+    }
+}
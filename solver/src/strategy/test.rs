@@ -1,15 +1,17 @@
 use std::cmp::Reverse;
+use std::collections::HashSet;
 
 use indexmap::IndexSet;
 
 use crate::index::{AsIndex, IndexedVec};
 
 use super::{
-    valuation, GetRelevance, NodeId, NodeMap, ParityGraph, PlayProfile, Player, Relevance, Strategy,
+    solve_parity_game, update_valuation, valuation, GetRelevance, NodeId, NodeMap, ParityGraph,
+    ParitySolution, PlayProfile, Player, Relevance, Strategy,
 };
 
 #[derive(Default)]
-struct TestGame {
+pub(crate) struct TestGame {
     relevance: IndexedVec<NodeId, usize>,
     players: IndexedVec<NodeId, Player>,
     successors: IndexedVec<NodeId, Vec<NodeId>>,
@@ -66,7 +68,7 @@ impl Strategy for TestStrategy {
     }
 }
 
-fn parse_test(source: &str) -> TestGame {
+pub(crate) fn parse_test(source: &str) -> TestGame {
     pub struct Node {
         pub id: usize,
         pub relevance: usize,
@@ -112,6 +114,29 @@ fn parse_test(source: &str) -> TestGame {
     TestGame { relevance, players, successors, predecessors, nodes_by_reward }
 }
 
+fn parse_sol(source: &str) -> Vec<(NodeId, Player)> {
+    source
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let mut fields = line.strip_suffix(';').unwrap().split(' ');
+            let id = fields.next().unwrap().parse().unwrap();
+            let player = if fields.next().unwrap() == "0" { Player::P0 } else { Player::P1 };
+            (NodeId(id), player)
+        })
+        .collect()
+}
+
+impl TestStrategy {
+    fn new(direct: NodeMap<NodeId>) -> Self {
+        let mut inverse = NodeMap::default();
+        for (&n, &m) in direct.iter() {
+            inverse.entry(m).or_insert_with(Vec::new).push(n);
+        }
+        Self { direct, inverse }
+    }
+}
+
 fn run_valuation_test(game: &TestGame) {
     let direct_strategy = game
         .players
@@ -127,7 +152,44 @@ fn run_valuation_test(game: &TestGame) {
 
     let (profiles, final_strategy) = valuation(game, &strategy);
 
-    verify_valuation(&strategy, &profiles, &final_strategy, &game);
+    verify_valuation(&strategy, &profiles, &final_strategy, game);
+}
+
+/// Switches the player 0 nodes selected by `switch` to their first successor, evaluates them
+/// incrementally and checks the result against a full valuation. Returns the number of nodes
+/// evaluated again.
+fn run_update_valuation_test(game: &TestGame, switch: impl Fn(NodeId) -> bool) -> usize {
+    let mut direct_strategy = game
+        .players
+        .enumerate()
+        .filter(|(_, &p)| p == Player::P0)
+        .map(|(n, _)| (n, *game.successors[n].last().unwrap()))
+        .collect::<NodeMap<_>>();
+    let strategy = TestStrategy::new(direct_strategy.clone());
+    let (mut profiles, mut final_strategy) = valuation(game, &strategy);
+
+    let mut switched = Vec::new();
+    for (&n, m) in direct_strategy.iter_mut().filter(|(&n, _)| switch(n)) {
+        if *m != game.successors[n][0] {
+            *m = game.successors[n][0];
+            switched.push(n);
+        }
+    }
+    let strategy = TestStrategy::new(direct_strategy);
+
+    let count = update_valuation(game, &strategy, switched, &mut profiles, &mut final_strategy);
+
+    verify_valuation(&strategy, &profiles, &final_strategy, game);
+
+    // The profiles must be the same as those of a full valuation.
+    let (expected, _) = valuation(game, &strategy);
+    for (n, profile) in expected.enumerate() {
+        assert_eq!(profile.most_relevant, profiles[n].most_relevant, "Node {n:?}");
+        assert_eq!(profile.relevant_before, profiles[n].relevant_before, "Node {n:?}");
+        assert_eq!(profile.count_before, profiles[n].count_before, "Node {n:?}");
+    }
+
+    count
 }
 
 pub fn verify_valuation<S: Strategy>(
@@ -178,6 +240,62 @@ pub fn verify_valuation<S: Strategy>(
     }
 }
 
+/// Checks that `solution` has the expected winners, that the winning regions are closed under
+/// the strategies of their winners, and that all the cycles allowed by them are won by the
+/// expected player.
+fn check_solution(game: &TestGame, solution: &ParitySolution, sol: &[(NodeId, Player)]) {
+    for &(n, winner) in sol {
+        assert_eq!(solution.winner[n], winner, "Node {n:?}");
+    }
+
+    let nodes = (0..game.node_count()).map(NodeId);
+    // Successors of a node when its winner plays according to its strategy.
+    let succs = |n: NodeId| -> Vec<NodeId> {
+        match game.player_of(n) == solution.winner[n] {
+            true => vec![solution.strategy[n]],
+            false => game.successors_of(n).collect(),
+        }
+    };
+
+    for n in nodes.clone() {
+        assert!(succs(n).iter().all(|&m| solution.winner[m] == solution.winner[n]), "Node {n:?}");
+    }
+
+    // A node with a priority favouring the opponent must not reach itself through nodes
+    // with lower or equal priority.
+    let priority = |n| game.relevance_of(n).priority;
+    for n in nodes.filter(|&n| game.relevance_of(n).player() != solution.winner[n]) {
+        let (mut stack, mut seen) = (succs(n), HashSet::new());
+        while let Some(m) = stack.pop() {
+            assert_ne!(m, n, "Node {n:?} is on a cycle won by the opponent");
+            if priority(m) <= priority(n) && seen.insert(m) {
+                stack.extend(succs(m));
+            }
+        }
+    }
+}
+
+/// Solves the games in the parity tests with `solve` and checks the solutions against theirs.
+pub(crate) fn test_solver(solve: fn(&TestGame) -> ParitySolution) {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../parity/tests/");
+    for e in std::fs::read_dir(dir).unwrap() {
+        let e = e.unwrap();
+
+        let name = e.file_name().into_string().unwrap();
+        let path = e.path();
+        if name == ".gitignore" || name.ends_with(".sol") {
+            continue;
+        }
+
+        let game = parse_test(&std::fs::read_to_string(&path).unwrap());
+        let sol = parse_sol(&std::fs::read_to_string(path.with_extension("sol")).unwrap());
+        if let Err(e) = std::panic::catch_unwind(|| check_solution(&game, &solve(&game), &sol)) {
+            eprintln!("Test {name} failed");
+            std::panic::resume_unwind(e);
+        }
+    }
+}
+
 #[test]
 fn test_all() {
     let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../parity/tests/");
@@ -197,3 +315,49 @@ fn test_all() {
         }
     }
 }
+
+#[test]
+fn strategy_iteration() {
+    test_solver(solve_parity_game);
+}
+
+#[test]
+fn update_valuation_all() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../parity/tests/");
+    for e in std::fs::read_dir(dir).unwrap() {
+        let e = e.unwrap();
+
+        let name = e.file_name().into_string().unwrap();
+        if name == ".gitignore" || name.ends_with(".sol") {
+            continue;
+        }
+
+        let input = std::fs::read_to_string(e.path()).unwrap();
+        let game = parse_test(&input);
+        for modulo in [1, 3, 7] {
+            let switch = |n: NodeId| n.to_usize().is_multiple_of(modulo);
+            if let Err(e) = std::panic::catch_unwind(|| run_update_valuation_test(&game, switch)) {
+                eprintln!("Test {name} failed switching every {modulo} nodes");
+                std::panic::resume_unwind(e);
+            }
+        }
+    }
+}
+
+#[test]
+fn update_valuation_only_affected() {
+    // The cycle 0 -> 1 -> 2 -> 0 can't reach 4, so switching 4 from 5 to 3 only affects the
+    // nodes that can reach it, that is 3 and 4.
+    let game = parse_test(
+        "parity 6;
+0 2 1 1;
+1 3 0 2;
+2 4 1 0;
+3 5 1 4,0;
+4 1 0 3,5;
+5 6 1 5;",
+    );
+    let count = run_update_valuation_test(&game, |n| n == NodeId(4));
+    assert_eq!(count, 2);
+    assert!(count < game.node_count());
+}
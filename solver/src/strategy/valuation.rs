@@ -1,4 +1,5 @@
-use std::collections::VecDeque;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
 
 use crate::index::IndexedVec;
 
@@ -9,28 +10,26 @@ use super::{
 struct Graph<'a, S: Strategy> {
     game: &'a S::Graph,
     strategy: &'a S,
+    /// If present, the only nodes whose predecessors are considered. They must be closed
+    /// under predecessors.
+    nodes: Option<&'a Set<NodeId>>,
 }
 
 impl<'a, S: Strategy> Graph<'a, S> {
     fn predecessors_of(&self, n: NodeId) -> impl Iterator<Item = NodeId> + '_ {
-        self.strategy.predecessors_of(n, self.game)
+        let nodes = self.nodes;
+        self.strategy
+            .predecessors_of(n, self.game)
+            .filter(move |v| nodes.is_none_or(|ns| ns.contains(v)))
     }
 
     fn successors_of(&self, n: NodeId) -> impl Iterator<Item = NodeId> + 'a {
         self.strategy.successors_of(n, self.game)
     }
 
-    fn node_count(&self) -> usize {
-        self.game.node_count()
-    }
-
     fn relevance_of(&self, n: NodeId) -> Relevance {
         self.game.relevance_of(n)
     }
-
-    fn nodes_sorted_by_reward(&self) -> impl Iterator<Item = NodeId> + 'a {
-        self.game.nodes_sorted_by_reward()
-    }
 }
 
 pub fn valuation<S: Strategy>(
@@ -38,16 +37,72 @@ pub fn valuation<S: Strategy>(
     strategy: &S,
 ) -> (IndexedVec<NodeId, PlayProfile>, IndexedVec<NodeId, NodeId>) {
     // Build graph with p0 moves restricted to the given strategy.
-    let graph = &Graph { game, strategy };
+    let graph = &Graph { game, strategy, nodes: None };
+
+    let mut profiles = IndexedVec::from(vec![PlayProfile::default(); game.node_count()]);
+    let mut final_strategy = IndexedVec::from(vec![NodeId(usize::MAX); game.node_count()]);
+
+    let exits = NodeMap::default();
+    evaluate(graph, game.nodes_sorted_by_reward(), &exits, &mut profiles, &mut final_strategy);
+
+    (profiles, final_strategy)
+}
+
+/// Updates the result of a previous `valuation` after the strategy changed only for the
+/// `changed` nodes, while the rest of the graph stayed the same.
+///
+/// Only the nodes that can reach a changed node can see their play affected, so only these are
+/// evaluated again. The others can't reach them, so their profiles and final strategy are kept
+/// and used as fixed values for the plays leaving the affected nodes. Returns the number of
+/// nodes evaluated again.
+pub fn update_valuation<S: Strategy>(
+    game: &S::Graph,
+    strategy: &S,
+    changed: impl IntoIterator<Item = NodeId>,
+    profiles: &mut IndexedVec<NodeId, PlayProfile>,
+    final_strategy: &mut IndexedVec<NodeId, NodeId>,
+) -> usize {
+    let graph = &Graph { game, strategy, nodes: None };
+
+    let mut reach = Reach { stack: Vec::new(), set: Set::default() };
+    let affected = reach.reach_all(changed, |n| graph.predecessors_of(n));
+
+    // A play leaving the affected nodes continues like the one of the unaffected node it moves
+    // to, so these nodes are grouped by the most relevant node of their cycle.
+    let mut exits = NodeMap::<Set<NodeId>>::default();
+    for &n in affected.iter() {
+        profiles[n] = PlayProfile::default();
+        for m in graph.successors_of(n).filter(|m| !affected.contains(m)) {
+            exits.entry(profiles[m].most_relevant).or_default().insert(m);
+        }
+    }
+
+    let mut order = affected.iter().chain(exits.keys()).copied().collect::<Vec<_>>();
+    order.sort_unstable_by_key(|&n| game.reward_of(n));
+
+    let graph = &Graph { game, strategy, nodes: Some(affected) };
+    evaluate(graph, order, &exits, profiles, final_strategy);
+
+    affected.len()
+}
 
+/// Evaluates the nodes in the order of their reward. The keys of `exits` are the most relevant
+/// nodes of cycles that were evaluated before, mapped to the nodes of those cycles' plays that
+/// the evaluated nodes can move to. These keep their profiles.
+fn evaluate<S: Strategy>(
+    graph: &Graph<S>,
+    nodes_sorted_by_reward: impl IntoIterator<Item = NodeId>,
+    exits: &NodeMap<Set<NodeId>>,
+    profiles: &mut IndexedVec<NodeId, PlayProfile>,
+    final_strategy: &mut IndexedVec<NodeId, NodeId>,
+) {
     let mut evaluated = Set::default();
-    let mut profiles = IndexedVec::from(vec![PlayProfile::default(); graph.node_count()]);
-    let mut final_strategy = IndexedVec::from(vec![NodeId(usize::MAX); graph.node_count()]);
+    let mut reach_exits = Reach { stack: Vec::new(), set: Set::default() };
 
     // Iterate by reward order, i.e. first nodes that are more in favour of player 1.
     // At each iteration we will try to fix all the loops that go through w, if w is not
     // already part of one.
-    for w in graph.nodes_sorted_by_reward() {
+    for w in nodes_sorted_by_reward {
         // Ignore already evaluated nodes
         if evaluated.contains(&w) {
             continue;
@@ -57,6 +112,17 @@ pub fn valuation<S: Strategy>(
         let preds_of = |n| graph.predecessors_of(n).filter(|v| !evaluated.contains(v));
         let succs_of = |n| graph.successors_of(n).filter(|v| !evaluated.contains(v));
 
+        // The cycle of w was already evaluated, so only the nodes that can reach it through
+        // its exits are left.
+        if let Some(exits) = exits.get(&w) {
+            let k_set = reach_exits.reach_all(exits.iter().copied(), preds_of);
+            if k_set.len() > exits.len() {
+                exits_subevaluation(graph, w, k_set, exits, profiles, final_strategy);
+                evaluated.extend(k_set.iter().copied());
+            }
+            continue;
+        }
+
         // Find all nodes v <= w that can reach w
         let rel_of = |v| graph.relevance_of(v);
         let w_rel = rel_of(w);
@@ -72,14 +138,12 @@ pub fn valuation<S: Strategy>(
 
         // Subevaluation: force all cycles that contain w to happen,
         // with the best path possible.
-        subevaluation(graph, w, &k_set, &mut profiles, &mut final_strategy);
+        subevaluation(graph, w, &k_set, profiles, final_strategy);
 
         // Equivalent to removing edges from K to V \ K,
         // as it will make sure they will never get explored again.
         evaluated.extend(k_set);
     }
-
-    (profiles, final_strategy)
 }
 
 /// A graph restricted to only some nodes (k) and with some edges removed.
@@ -156,7 +220,7 @@ fn subevaluation(
     let w_relevance = graph.relevance_of(w);
 
     // All these nodes will be part of cycles that contain w as most relevant node.
-    for &v in &*graph.k_nodes {
+    for &v in graph.k_nodes {
         profiles[v].most_relevant = w;
     }
 
@@ -191,6 +255,133 @@ fn subevaluation(
     }
 }
 
+/// Like `subevaluation`, for the nodes in `k_set` that reach `w` through the `exits`. These
+/// are part of `k_set` but keep their profiles, which tell the nodes more relevant than `w`
+/// visited by their plays and their distance from `w`.
+fn exits_subevaluation(
+    graph: &Graph<impl Strategy>,
+    w: NodeId,
+    k_set: &Set<NodeId>,
+    exits: &Set<NodeId>,
+    profiles: &mut IndexedVec<NodeId, PlayProfile>,
+    final_strategy: &mut IndexedVec<NodeId, NodeId>,
+) {
+    let k_nodes = k_set.iter().copied().filter(|v| !exits.contains(v)).collect::<Vec<_>>();
+
+    let mut graph = RestrictedGraph {
+        base: graph,
+        k_nodes: &k_nodes,
+        k_set,
+        removed_edges: Set::default(),
+        removed_successors_count: NodeMap::default(),
+    };
+
+    // The plays from the exits are fixed, so only the edges reaching them are considered.
+    for &x in exits {
+        for next in graph.all_successors_of(x) {
+            graph.remove_edge(x, next);
+        }
+    }
+
+    for &v in graph.k_nodes {
+        profiles[v].most_relevant = w;
+    }
+
+    // Nodes more relevant than w, either in K or visited by the plays from the exits.
+    let w_relevance = graph.relevance_of(w);
+    let mut relevant = (graph.k_nodes.iter().copied())
+        .filter(|&v| graph.relevance_of(v) > w_relevance)
+        .chain(exits.iter().flat_map(|&x| profiles[x].relevant_before.iter().copied()))
+        .collect::<Vec<_>>();
+    relevant.sort_unstable_by_key(|&v| Reverse(graph.relevance_of(v)));
+    relevant.dedup();
+
+    let mut reach = Reach { stack: Vec::new(), set: Set::default() };
+
+    for u in relevant {
+        let in_k = k_set.contains(&u) && !exits.contains(&u);
+        let through_u = |x: &&NodeId| profiles[**x].relevant_before.contains(&u);
+
+        match graph.relevance_of(u).player() {
+            Player::P0 => {
+                // Find nodes that can reach w without going through u, that is those reaching
+                // an exit whose play doesn't visit it.
+                let start = exits.iter().filter(|x| !through_u(x)).copied();
+                let u_set =
+                    reach.reach_all(start, |n| graph.predecessors_of(n).filter(|&v| v != u));
+
+                for &v in graph.k_nodes.iter().filter(|&v| !u_set.contains(v)) {
+                    profiles[v].relevant_before.push(u);
+                }
+
+                let sources = u_set.iter().chain(in_k.then_some(&u));
+                for &v in sources.filter(|v| !exits.contains(*v)) {
+                    for next in graph.all_successors_of(v).filter(|n| !u_set.contains(n)) {
+                        graph.remove_edge(v, next);
+                    }
+                }
+            }
+            Player::P1 => {
+                // Find nodes that can reach u, either directly or through an exit visiting it.
+                let start = match in_k {
+                    true => vec![u],
+                    false => exits.iter().filter(through_u).copied().collect(),
+                };
+                let u_set = reach.reach_all(start, |n| graph.predecessors_of(n));
+
+                for &v in u_set.iter().filter(|v| !exits.contains(*v)) {
+                    profiles[v].relevant_before.push(u);
+                }
+
+                for &v in u_set.iter().filter(|&&v| v != u && !exits.contains(&v)) {
+                    for next in graph.all_successors_of(v).filter(|n| !u_set.contains(n)) {
+                        graph.remove_edge(v, next);
+                    }
+                }
+            }
+        }
+    }
+
+    // Like `set_maximal_distances` and `set_minimal_distances`, starting from the exits in
+    // the order of their distance from w.
+    let mut queue = exits
+        .iter()
+        .map(|&x| Reverse((profiles[x].count_before, x, final_strategy[x])))
+        .collect::<BinaryHeap<_>>();
+    match w_relevance.player() {
+        Player::P0 => {
+            let mut remaining_successors = graph
+                .k_nodes
+                .iter()
+                .map(|&v| (v, graph.successors_count_of(v)))
+                .collect::<NodeMap<_>>();
+            while let Some(Reverse((d, v, _))) = queue.pop() {
+                for u in graph.predecessors_of(v) {
+                    let remaining = remaining_successors.get_mut(&u).unwrap();
+                    *remaining -= 1;
+                    if *remaining == 0 {
+                        profiles[u].count_before = d + 1;
+                        final_strategy[u] = v;
+                        queue.push(Reverse((d + 1, u, v)));
+                    }
+                }
+            }
+        }
+        Player::P1 => {
+            let mut seen = Set::default();
+            while let Some(Reverse((d, v, succ))) = queue.pop() {
+                if seen.insert(v) {
+                    if !exits.contains(&v) {
+                        profiles[v].count_before = d;
+                        final_strategy[v] = succ;
+                    }
+                    queue.extend(graph.predecessors_of(v).map(|u| Reverse((d + 1, u, v))));
+                }
+            }
+        }
+    }
+}
+
 /// Prevent any path that can go through u from doing so.
 fn prevent_paths(
     graph: &mut RestrictedGraph<impl Strategy>,
@@ -247,14 +438,29 @@ struct Reach {
 }
 
 impl Reach {
-    fn reach<F, I>(&mut self, start: NodeId, mut explore: F) -> &Set<NodeId>
+    fn reach<F, I>(&mut self, start: NodeId, explore: F) -> &Set<NodeId>
+    where
+        F: FnMut(NodeId) -> I,
+        I: Iterator<Item = NodeId>,
+    {
+        self.reach_all([start], explore)
+    }
+
+    fn reach_all<F, I>(
+        &mut self,
+        start: impl IntoIterator<Item = NodeId>,
+        mut explore: F,
+    ) -> &Set<NodeId>
     where
         F: FnMut(NodeId) -> I,
         I: Iterator<Item = NodeId>,
     {
-        self.stack.push(start);
         self.set.clear();
-        self.set.insert(start);
+        for start in start {
+            if self.set.insert(start) {
+                self.stack.push(start);
+            }
+        }
 
         // DFS according to explore
         while let Some(node) = self.stack.pop() {
@@ -0,0 +1,269 @@
+use crate::index::{AsIndex, IndexedVec};
+use crate::tarjan::Tarjan;
+
+use super::eq::{Expr, FixEq, FixType, VarId};
+
+/// The dependencies between the variables of a system of equations, and the way they nest.
+pub struct Dependencies {
+    /// Variables occurring in the equation of each variable, including in function arguments.
+    pub deps: IndexedVec<VarId, Vec<VarId>>,
+    /// Strongly connected components of the dependency graph, each only depending on itself
+    /// and the previous ones. Together they contain all the variables.
+    pub sccs: Vec<Component>,
+}
+
+/// A strongly connected component of the dependency graph, or of the graph without the
+/// outermost variables of an enclosing component.
+pub struct Component {
+    /// Variables in the component, from the innermost to the outermost.
+    pub vars: Vec<VarId>,
+    /// Fixpoint type of the outermost variable.
+    pub fix_type: FixType,
+    /// Position in `vars` of the outermost block of variables, made of the outermost ones with
+    /// the same fixpoint type as the outermost variable.
+    pub block_start: usize,
+    /// Number of alternations between fixpoint types along nested cycles, counting the
+    /// outermost one. This is 0 if the component has no cycles.
+    pub alternation_depth: usize,
+    /// Components with cycles that remain after removing the outermost variables with the
+    /// same fixpoint type as the outermost one.
+    pub nested: Vec<Component>,
+}
+
+impl Dependencies {
+    pub fn new(eqs: &IndexedVec<VarId, FixEq>) -> Self {
+        let deps = eqs
+            .iter()
+            .map(|eq| {
+                let mut vars = Vec::new();
+                collect_vars(&eq.expr, &mut vars);
+                vars.sort_unstable();
+                vars.dedup();
+                vars
+            })
+            .collect::<IndexedVec<VarId, _>>();
+
+        let succs =
+            deps.iter().map(|d| d.iter().map(|v| v.to_usize()).collect()).collect::<Vec<_>>();
+        let mut tarjan = Tarjan::new(&succs);
+        let nodes = (0..eqs.len()).collect::<Vec<_>>();
+        let sccs = tarjan
+            .sccs(&nodes)
+            .into_iter()
+            .map(|scc| Component::new(eqs, &succs, &mut tarjan, scc))
+            .collect();
+
+        Self { deps, sccs }
+    }
+
+    /// The highest alternation depth of the components.
+    pub fn alternation_depth(&self) -> usize {
+        self.sccs.iter().map(|scc| scc.alternation_depth).max().unwrap_or(0)
+    }
+}
+
+impl Component {
+    fn new(
+        eqs: &IndexedVec<VarId, FixEq>,
+        succs: &[Vec<usize>],
+        tarjan: &mut Tarjan,
+        mut nodes: Vec<usize>,
+    ) -> Self {
+        nodes.sort_unstable();
+        let vars = nodes.iter().map(|&n| VarId(n)).collect::<Vec<_>>();
+        let fix_type_of = |n: usize| eqs[VarId(n)].fix_type;
+        let top = *nodes.last().unwrap();
+        let fix_type = fix_type_of(top);
+
+        let is_cycle = nodes.len() > 1 || succs[top].contains(&top);
+        if !is_cycle {
+            return Self {
+                vars,
+                fix_type,
+                block_start: 0,
+                alternation_depth: 0,
+                nested: Vec::new(),
+            };
+        }
+
+        // Cycles through the outermost variables are won according to `fix_type`, the others
+        // are in the nested components and alternate if their outermost fixpoint type differs.
+        let block_start =
+            nodes.iter().rposition(|&n| fix_type_of(n) != fix_type).map_or(0, |p| p + 1);
+        let nested = tarjan
+            .sccs(&nodes[..block_start])
+            .into_iter()
+            .map(|scc| Self::new(eqs, succs, tarjan, scc))
+            .filter(|c| c.alternation_depth > 0)
+            .collect::<Vec<_>>();
+        let alternation_depth = nested
+            .iter()
+            .map(|c| c.alternation_depth + (c.fix_type != fix_type) as usize)
+            .fold(1, usize::max);
+
+        Self { vars, fix_type, block_start, alternation_depth, nested }
+    }
+
+    /// Assigns the priorities of the variables in cycles of the component, returning the
+    /// highest one.
+    fn assign_priorities(&self, priorities: &mut IndexedVec<VarId, usize>) -> usize {
+        if self.alternation_depth == 0 {
+            return 0;
+        }
+
+        // The outermost block must have at least the priority of the nested components, with
+        // the parity of its fixpoint type.
+        let nested = self.nested.iter().map(|c| c.assign_priorities(priorities)).max();
+        let nested = nested.unwrap_or(0);
+        let priority = match self.fix_type {
+            FixType::Max => (nested + nested % 2).max(2),
+            FixType::Min => nested + 1 - nested % 2,
+        };
+        for &var in &self.vars[self.block_start..] {
+            priorities[var] = priority;
+        }
+        priority
+    }
+}
+
+/// Priorities for the positions of each variable, going from 1 to at most one more than the
+/// alternation depth. Even priorities are for greatest fixpoints and odd ones for least
+/// fixpoints, and the most relevant variable of every cycle between positions also has
+/// the highest priority in it, so they are won by the same player. Unlike the variable
+/// index this gives the same priority to most variables.
+#[derive(Clone)]
+pub struct Priorities {
+    priorities: IndexedVec<VarId, usize>,
+    // The variables with each priority, in increasing order.
+    vars: Vec<Vec<VarId>>,
+}
+
+impl Priorities {
+    pub fn new(eqs: &IndexedVec<VarId, FixEq>) -> Self {
+        let deps = Dependencies::new(eqs);
+
+        // Variables that are not in any cycle get the lowest priority.
+        let mut priorities = IndexedVec::from(vec![1; eqs.len()]);
+        for scc in &deps.sccs {
+            scc.assign_priorities(&mut priorities);
+        }
+
+        let max = priorities.iter().copied().max().unwrap_or(0);
+        let mut vars = vec![Vec::new(); max + 1];
+        for (var, &priority) in priorities.enumerate() {
+            vars[priority].push(var);
+        }
+
+        Self { priorities, vars }
+    }
+
+    pub fn of(&self, var: VarId) -> usize {
+        self.priorities[var]
+    }
+
+    /// The highest priority of a variable, or 0 if there are no variables.
+    pub fn max(&self) -> usize {
+        self.vars.len().saturating_sub(1)
+    }
+
+    /// The variables with the given priority, in increasing order.
+    pub fn vars_with(&self, priority: usize) -> &[VarId] {
+        self.vars.get(priority).map_or(&[], |vars| vars)
+    }
+}
+
+fn collect_vars(expr: &Expr, out: &mut Vec<VarId>) {
+    match expr {
+        Expr::Var(var) => out.push(*var),
+        Expr::And(children) | Expr::Or(children) | Expr::Fun(_, children) => {
+            children.iter().for_each(|child| collect_vars(child, out))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dependencies, Priorities};
+    use crate::index::IndexedVec;
+    use crate::symbolic::eq::{Expr, FixEq, FixType, FunId, VarId};
+
+    fn var(i: usize) -> Expr {
+        Expr::Var(VarId(i))
+    }
+
+    fn eqs(eqs: Vec<(FixType, Expr)>) -> IndexedVec<VarId, FixEq> {
+        eqs.into_iter().map(|(fix_type, expr)| FixEq { fix_type, expr }).collect()
+    }
+
+    fn vars(is: impl IntoIterator<Item = usize>) -> Vec<VarId> {
+        is.into_iter().map(VarId).collect()
+    }
+
+    #[test]
+    fn no_cycles() {
+        // X0 = true, X1 = f(X0)
+        let deps = Dependencies::new(&eqs(vec![
+            (FixType::Min, Expr::TOP),
+            (FixType::Max, Expr::Fun(FunId(0), vec![var(0)])),
+        ]));
+
+        assert_eq!(deps.deps[VarId(1)], vars([0]));
+        assert_eq!(
+            deps.sccs.iter().map(|c| c.vars.clone()).collect::<Vec<_>>(),
+            [vars([0]), vars([1])]
+        );
+        assert_eq!(deps.alternation_depth(), 0);
+    }
+
+    #[test]
+    fn nested_alternations() {
+        // X0 = X1 || X0, X1 = X0 && X2, X2 = f(X1, X3), X3 = X3 && X4, X4 = X3
+        let deps = Dependencies::new(&eqs(vec![
+            (FixType::Min, Expr::Or(vec![var(1), var(0)])),
+            (FixType::Max, Expr::And(vec![var(0), var(2)])),
+            (FixType::Max, Expr::Fun(FunId(0), vec![var(1), var(3)])),
+            (FixType::Min, Expr::And(vec![var(3), var(4)])),
+            (FixType::Min, var(3)),
+        ]));
+
+        // X3 and X4 don't depend on the others, which form a component where the cycle of X0
+        // alternates with the cycle through X1 and X2.
+        let [inner, outer] = &deps.sccs[..] else { panic!("Expected two components") };
+        assert_eq!(inner.vars, vars([3, 4]));
+        assert_eq!(inner.alternation_depth, 1);
+        assert_eq!(outer.vars, vars([0, 1, 2]));
+        assert_eq!(outer.fix_type, FixType::Max);
+        assert_eq!(outer.alternation_depth, 2);
+        assert_eq!(outer.nested.len(), 1);
+        assert_eq!(outer.nested[0].vars, vars([0]));
+        assert_eq!(deps.alternation_depth(), 2);
+
+        // Without the self loop of X0 there is no alternation.
+        let deps = Dependencies::new(&eqs(vec![
+            (FixType::Min, var(1)),
+            (FixType::Max, Expr::And(vec![var(0), var(2)])),
+            (FixType::Max, var(1)),
+        ]));
+        assert_eq!(deps.alternation_depth(), 1);
+        assert!(deps.sccs[0].nested.is_empty());
+    }
+
+    #[test]
+    fn compact_priorities() {
+        // X0 = X0 && X1, X1 = X0 || X2, X2 = X1 || X2, X3 = X2 && X3, X4 = X3
+        let priorities = Priorities::new(&eqs(vec![
+            (FixType::Max, Expr::And(vec![var(0), var(1)])),
+            (FixType::Max, Expr::Or(vec![var(0), var(2)])),
+            (FixType::Min, Expr::Or(vec![var(1), var(2)])),
+            (FixType::Min, Expr::And(vec![var(2), var(3)])),
+            (FixType::Max, var(3)),
+        ]));
+
+        // X0 and X1 are nested in X2, while X3 and X4 are on their own.
+        let of = |i| priorities.of(VarId(i));
+        assert_eq!([0, 1, 2, 3, 4].map(of), [2, 2, 3, 1, 1]);
+        assert_eq!(priorities.max(), 3);
+        assert_eq!(priorities.vars_with(1), vars([3, 4]));
+        assert_eq!(priorities.vars_with(0), []);
+    }
+}
@@ -0,0 +1,459 @@
+use std::mem;
+
+use rustc_hash::FxHashSet;
+
+use crate::index::new_index;
+use crate::sync::Shared;
+use crate::{Map, Set};
+
+use super::formula::{FormulaId, FormulaRef, Node};
+use super::moves::{Assumption, P0Pos};
+
+new_index!(pub index BddId);
+
+/// A reduced ordered binary decision diagram over the atoms of a formula, ordered like the
+/// positions of a move. Unlike the DNF of the formula its size doesn't explode for
+/// conjunctions of many disjunctions over the same atoms.
+#[derive(Clone)]
+pub struct Bdd {
+    // The atom of each level.
+    vars: Vec<P0Pos>,
+    nodes: Set<BddNode>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct BddNode {
+    level: usize,
+    low: BddId,
+    high: BddId,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Op {
+    And,
+    Or,
+}
+
+impl Bdd {
+    pub const FALSE: BddId = BddId(0);
+    pub const TRUE: BddId = BddId(1);
+
+    // The level of the terminals, after all the atoms.
+    const TERMINAL: usize = usize::MAX;
+
+    /// Builds the BDD of the formula `f`, returning it together with its root.
+    pub fn from_formula(f: &FormulaRef) -> (Self, BddId) {
+        let mut vars = Vec::new();
+        collect_atoms(f, &mut FxHashSet::default(), &mut vars);
+        vars.sort_unstable_by_key(|&P0Pos { b, i }| (i, b));
+        let levels = vars.iter().enumerate().map(|(level, &pos)| (pos, level)).collect();
+
+        let nodes = Set::from_iter([Self::FALSE, Self::TRUE].map(|n| BddNode {
+            level: Self::TERMINAL,
+            low: n,
+            high: n,
+        }));
+        let mut bdd = Self { vars, nodes };
+        let root = bdd.build(f, &levels, &mut Map::default(), &mut Map::default());
+        (bdd, root)
+    }
+
+    /// Number of nodes, including the terminals and the ones no longer reachable after
+    /// restricting the diagram.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the function of `n` holds when exactly the positions in `positions` are true.
+    pub fn holds(&self, n: BddId, positions: &[P0Pos]) -> bool {
+        let positive = self.vars.iter().map(|pos| positions.contains(pos)).collect::<Vec<_>>();
+        self.holds_with(n, &positive)
+    }
+
+    /// The node for the function of `n` where the positions that are assumed to be winning
+    /// or losing are replaced by true or false respectively.
+    pub fn restrict(&mut self, n: BddId, mut assumption: impl FnMut(P0Pos) -> Assumption) -> BddId {
+        let mut assumptions = vec![None; self.vars.len()];
+        let mut assumption = |bdd: &Self, level: usize| {
+            *assumptions[level].get_or_insert_with(|| assumption(bdd.vars[level]))
+        };
+        self.restrict_with(n, &mut assumption, &mut Map::default())
+    }
+
+    fn restrict_with(
+        &mut self,
+        n: BddId,
+        assumption: &mut impl FnMut(&Self, usize) -> Assumption,
+        memo: &mut Map<BddId, BddId>,
+    ) -> BddId {
+        let node = self.nodes[n.0];
+        if node.level == Self::TERMINAL {
+            return n;
+        }
+        if let Some(&restricted) = memo.get(&n) {
+            return restricted;
+        }
+        let restricted = match assumption(self, node.level) {
+            Assumption::Win => self.restrict_with(node.high, assumption, memo),
+            Assumption::Lose => self.restrict_with(node.low, assumption, memo),
+            Assumption::Unknown => {
+                let low = self.restrict_with(node.low, assumption, memo);
+                let high = self.restrict_with(node.high, assumption, memo);
+                self.mk(node.level, low, high)
+            }
+        };
+        memo.insert(n, restricted);
+        restricted
+    }
+
+    fn build(
+        &mut self,
+        f: &FormulaRef,
+        levels: &Map<P0Pos, usize>,
+        built: &mut Map<FormulaId, BddId>,
+        applied: &mut Map<(Op, BddId, BddId), BddId>,
+    ) -> BddId {
+        if let Some(&n) = built.get(&f.id()) {
+            return n;
+        }
+        let n = match *f.node() {
+            Node::Atom(b, i) => self.mk(levels[&P0Pos { b, i }], Self::FALSE, Self::TRUE),
+            Node::And(ref children) => children.iter().fold(Self::TRUE, |acc, child| {
+                let child = self.build(child, levels, built, applied);
+                self.apply(Op::And, acc, child, applied)
+            }),
+            Node::Or(ref children) => children.iter().fold(Self::FALSE, |acc, child| {
+                let child = self.build(child, levels, built, applied);
+                self.apply(Op::Or, acc, child, applied)
+            }),
+        };
+        built.insert(f.id(), n);
+        n
+    }
+
+    fn apply(
+        &mut self,
+        op: Op,
+        a: BddId,
+        b: BddId,
+        memo: &mut Map<(Op, BddId, BddId), BddId>,
+    ) -> BddId {
+        let (neutral, absorbing) = match op {
+            Op::And => (Self::TRUE, Self::FALSE),
+            Op::Or => (Self::FALSE, Self::TRUE),
+        };
+        match () {
+            _ if a == absorbing || b == absorbing => return absorbing,
+            _ if a == neutral || a == b => return b,
+            _ if b == neutral => return a,
+            _ => {}
+        }
+
+        let key = (op, a.min(b), a.max(b));
+        if let Some(&n) = memo.get(&key) {
+            return n;
+        }
+
+        let (na, nb) = (self.nodes[a.0], self.nodes[b.0]);
+        let level = na.level.min(nb.level);
+        let cofactors =
+            |node: BddNode, n| if node.level == level { (node.low, node.high) } else { (n, n) };
+        let ((a_low, a_high), (b_low, b_high)) = (cofactors(na, a), cofactors(nb, b));
+        let low = self.apply(op, a_low, b_low, memo);
+        let high = self.apply(op, a_high, b_high, memo);
+        let n = self.mk(level, low, high);
+        memo.insert(key, n);
+        n
+    }
+
+    fn mk(&mut self, level: usize, low: BddId, high: BddId) -> BddId {
+        match low == high {
+            true => low,
+            false => BddId(self.nodes.insert_full(BddNode { level, low, high }).0),
+        }
+    }
+
+    fn holds_with(&self, mut n: BddId, positive: &[bool]) -> bool {
+        loop {
+            let node = self.nodes[n.0];
+            if node.level == Self::TERMINAL {
+                return n == Self::TRUE;
+            }
+            n = if positive[node.level] { node.high } else { node.low };
+        }
+    }
+}
+
+fn collect_atoms(f: &FormulaRef, visited: &mut FxHashSet<FormulaId>, out: &mut Vec<P0Pos>) {
+    if !visited.insert(f.id()) {
+        return;
+    }
+    match *f.node() {
+        Node::Atom(b, i) => out.push(P0Pos { b, i }),
+        Node::And(ref children) | Node::Or(ref children) => {
+            children.iter().for_each(|child| collect_atoms(child, visited, out))
+        }
+    }
+}
+
+// Depth-first enumeration of the minimal sets of positions satisfying a BDD, which must be
+// monotone. Paths to the true terminal give the positions on their high edges, which always
+// satisfy the function, and the minimal sets are the ones where every high edge is needed,
+// that is the positions after it don't satisfy its low child.
+struct MinimalSets {
+    // The nodes on the current path, with whether their high edge is taken.
+    path: Vec<(BddId, bool)>,
+    // Whether the position of each level is in the current set.
+    positive: Vec<bool>,
+    started: bool,
+}
+
+impl MinimalSets {
+    fn new(bdd: &Bdd) -> Self {
+        Self { path: Vec::new(), positive: vec![false; bdd.vars.len()], started: false }
+    }
+
+    fn next(&mut self, bdd: &Bdd, root: BddId) -> Option<Vec<P0Pos>> {
+        let mut n = match mem::replace(&mut self.started, true) {
+            false => root,
+            true => self.backtrack(bdd)?,
+        };
+        loop {
+            while bdd.nodes[n.0].level != Bdd::TERMINAL {
+                self.path.push((n, false));
+                n = bdd.nodes[n.0].low;
+            }
+            if n == Bdd::TRUE && self.is_minimal(bdd) {
+                let set = self.path.iter().filter(|&&(_, high)| high);
+                return Some(set.map(|&(n, _)| bdd.vars[bdd.nodes[n.0].level]).collect());
+            }
+            n = self.backtrack(bdd)?;
+        }
+    }
+
+    // Takes the high edge of the deepest node whose low edge is being explored, returning
+    // its high child. High edges of nodes with a true low child never lead to minimal sets.
+    fn backtrack(&mut self, bdd: &Bdd) -> Option<BddId> {
+        while let Some((n, high)) = self.path.pop() {
+            let node = bdd.nodes[n.0];
+            if high {
+                self.positive[node.level] = false;
+            } else if node.low != Bdd::TRUE {
+                self.path.push((n, true));
+                self.positive[node.level] = true;
+                return Some(node.high);
+            }
+        }
+        None
+    }
+
+    // The low child of a node only depends on the positions after it, so it can be
+    // evaluated on the whole set.
+    fn is_minimal(&self, bdd: &Bdd) -> bool {
+        self.path
+            .iter()
+            .filter(|&&(_, high)| high)
+            .all(|&(n, _)| !bdd.holds_with(bdd.nodes[n.0].low, &self.positive))
+    }
+}
+
+/// Moves of a position enumerated from the BDD of its formula, as the minimal sets of
+/// positions satisfying it. The next move is found in advance to know when they are
+/// exhausted.
+pub(super) struct BddMoves {
+    bdd: Bdd,
+    root: BddId,
+    sets: MinimalSets,
+    // Moves returned so far, without the positions known to be winning.
+    returned: Set<Shared<[P0Pos]>>,
+    next: Option<Shared<[P0Pos]>>,
+}
+
+impl BddMoves {
+    pub(super) fn new(f: &FormulaRef) -> Self {
+        let (bdd, root) = Bdd::from_formula(f);
+        let sets = MinimalSets::new(&bdd);
+        let mut moves = Self { bdd, root, sets, returned: Set::default(), next: None };
+        moves.next = moves.find_next();
+        moves
+    }
+
+    pub(super) fn is_exhausted(&self) -> bool {
+        self.next.is_none()
+    }
+
+    /// Restricts the formula with the given assumptions. If this changes it the enumeration
+    /// restarts, skipping the moves that were already returned once the winning positions
+    /// are removed from them. Every minimal move of the restricted formula is either one of
+    /// them or is still to be returned.
+    pub(super) fn simplify(&mut self, mut assumption: impl FnMut(P0Pos) -> Assumption) {
+        if self.is_exhausted() {
+            return;
+        }
+
+        let root = self.bdd.restrict(self.root, &mut assumption);
+        if root == self.root {
+            return;
+        }
+
+        // Moves with losing positions can't be the same as the new ones.
+        self.returned = mem::take(&mut self.returned)
+            .into_iter()
+            .filter_map(|mov| {
+                let mut simplified = Vec::with_capacity(mov.len());
+                for &pos in mov.iter() {
+                    match assumption(pos) {
+                        Assumption::Win => {}
+                        Assumption::Lose => return None,
+                        Assumption::Unknown => simplified.push(pos),
+                    }
+                }
+                Some(simplified.into())
+            })
+            .collect();
+
+        self.root = root;
+        self.sets = MinimalSets::new(&self.bdd);
+        self.next = self.find_next();
+    }
+
+    fn find_next(&mut self) -> Option<Shared<[P0Pos]>> {
+        while let Some(set) = self.sets.next(&self.bdd, self.root) {
+            let mov = Shared::<[P0Pos]>::from(set);
+            if !self.returned.contains(&mov) {
+                return Some(mov);
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for BddMoves {
+    type Item = Shared<[P0Pos]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mov = self.next.take()?;
+        self.returned.insert(mov.clone());
+        self.next = self.find_next();
+        Some(mov)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bdd, BddMoves};
+    use crate::symbolic::eq::VarId;
+    use crate::symbolic::formula::{BasisElemId, Formula, FormulaDag};
+    use crate::symbolic::moves::{Assumption, P0Pos};
+
+    fn atom(i: usize) -> Formula {
+        Formula::Atom(BasisElemId(i), VarId(0))
+    }
+
+    fn pos(i: usize) -> P0Pos {
+        P0Pos { b: BasisElemId(i), i: VarId(0) }
+    }
+
+    fn sets(moves: impl Iterator<Item = impl AsRef<[P0Pos]>>) -> Vec<Vec<usize>> {
+        let mut sets =
+            moves.map(|mov| mov.as_ref().iter().map(|p| p.b.0).collect()).collect::<Vec<_>>();
+        sets.sort();
+        sets
+    }
+
+    #[test]
+    fn reduced_and_shared() {
+        // (0 | 1) & (0 | 2) & (1 | 0) has the same BDD as 0 | (1 & 2)
+        let mut dag = FormulaDag::new();
+        let f = dag.insert(&Formula::And(vec![
+            Formula::Or(vec![atom(0), atom(1)]),
+            Formula::Or(vec![atom(0), atom(2)]),
+            Formula::Or(vec![atom(1), atom(0)]),
+        ]));
+        let g = dag.insert(&Formula::Or(vec![atom(0), Formula::And(vec![atom(1), atom(2)])]));
+
+        let (bdd, root) = Bdd::from_formula(&f);
+        assert!(bdd.holds(root, &[pos(1), pos(2)]));
+        assert!(!bdd.holds(root, &[pos(1)]));
+
+        let moves = BddMoves::new(&f);
+        assert_eq!(sets(moves), sets(BddMoves::new(&g)));
+    }
+
+    #[test]
+    fn minimal_sets() {
+        // (0 & 2) | 1 | (0 & 1 & 3) | 2 & 3
+        let mut dag = FormulaDag::new();
+        let f = dag.insert(&Formula::Or(vec![
+            Formula::And(vec![atom(0), atom(2)]),
+            atom(1),
+            Formula::And(vec![atom(0), atom(1), atom(3)]),
+            Formula::And(vec![atom(2), atom(3)]),
+        ]));
+
+        assert_eq!(sets(BddMoves::new(&f)), [vec![0, 2], vec![1], vec![2, 3]]);
+    }
+
+    #[test]
+    fn restrict() {
+        // (0 | 1) & (2 | 3)
+        let mut dag = FormulaDag::new();
+        let f = dag.insert(&Formula::And(vec![
+            Formula::Or(vec![atom(0), atom(1)]),
+            Formula::Or(vec![atom(2), atom(3)]),
+        ]));
+        let mut moves = BddMoves::new(&f);
+        assert_eq!(sets(moves.next().into_iter()), [vec![1, 3]]);
+
+        // With 0 losing and 2 winning only 1 is left, which wasn't returned yet.
+        moves.simplify(|p| match p.b.0 {
+            0 => Assumption::Lose,
+            2 => Assumption::Win,
+            _ => Assumption::Unknown,
+        });
+        assert_eq!(sets(moves), [vec![1]]);
+
+        // Moves that only differ from the returned ones by winning positions are skipped.
+        let mut moves = BddMoves::new(&f);
+        moves.next();
+        moves.simplify(|p| match p.b.0 {
+            3 => Assumption::Win,
+            _ => Assumption::Unknown,
+        });
+        assert_eq!(sets(moves), [vec![0]]);
+
+        // Assuming everything is losing leaves no moves.
+        let mut moves = BddMoves::new(&f);
+        moves.simplify(|_| Assumption::Lose);
+        assert!(moves.is_exhausted());
+    }
+
+    #[test]
+    fn restrict_shared() {
+        // (0 | 1) & (2 | 3) & ... has a node per atom, but each of them is reached by
+        // exponentially many paths, so restricting it must not follow every path.
+        let mut dag = FormulaDag::new();
+        let pairs = (0..64).map(|k| Formula::Or(vec![atom(2 * k), atom(2 * k + 1)]));
+        let f = dag.insert(&Formula::And(pairs.collect()));
+        let (mut bdd, root) = Bdd::from_formula(&f);
+        let nodes = bdd.node_count();
+
+        assert_eq!(bdd.restrict(root, |_| Assumption::Unknown), root);
+        assert_eq!(bdd.node_count(), nodes);
+
+        // With the last pair losing the whole conjunction is false.
+        let lost = bdd.restrict(root, |p| match p.b.0 {
+            126 | 127 => Assumption::Lose,
+            _ => Assumption::Unknown,
+        });
+        assert_eq!(lost, Bdd::FALSE);
+
+        // With one position of the last pair winning only the other pairs are left.
+        let won = bdd.restrict(root, |p| match p.b.0 {
+            127 => Assumption::Win,
+            _ => Assumption::Unknown,
+        });
+        assert!(bdd.holds(won, &(0..63).map(|k| pos(2 * k)).collect::<Vec<_>>()));
+        assert!(!bdd.holds(won, &(1..63).map(|k| pos(2 * k)).collect::<Vec<_>>()));
+    }
+}
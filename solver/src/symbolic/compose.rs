@@ -1,66 +1,144 @@
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::collections::hash_map::Entry;
 
 use crate::index::{AsIndex, IndexedVec};
+use crate::sync::{Lock, Shared};
 use crate::Map;
 
+use super::analysis::Priorities;
 use super::eq::{Expr, FixEq, FixType, FunId, VarId};
-use super::formula::{simplify_and, simplify_or, BasisElemId, Formula};
+use super::formula::{BasisElemId, Formula, FormulaDag, FormulaRef};
+use super::moves::MovesRepr;
+
+/// Number of lookups that found the formula in a cache, and of those that had to compute it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl CacheStats {
+    fn record(stats: &Lock<Self>, hit: bool) {
+        let mut stats = stats.lock();
+        match hit {
+            true => stats.hits += 1,
+            false => stats.misses += 1,
+        }
+    }
+}
+
+/// Lazily computes the formula of a function for a given basis element.
+#[cfg(not(feature = "sync"))]
+pub type FormulaGenerator = Shared<dyn Fn(BasisElemId) -> Formula>;
+/// Lazily computes the formula of a function for a given basis element.
+#[cfg(feature = "sync")]
+pub type FormulaGenerator = Shared<dyn Fn(BasisElemId) -> Formula + Send + Sync>;
 
 #[derive(Clone)]
 pub struct FunsFormulas {
-    generators: IndexedVec<FunId, Rc<dyn Fn(BasisElemId) -> Formula>>,
-    cache: IndexedVec<FunId, RefCell<Map<BasisElemId, Rc<Formula>>>>,
+    generators: IndexedVec<FunId, FormulaGenerator>,
+    cache: IndexedVec<FunId, Lock<Map<BasisElemId, Shared<Formula>>>>,
+    table_lens: IndexedVec<FunId, Option<usize>>,
+    stats: Lock<CacheStats>,
 }
 
 impl FunsFormulas {
     pub fn new(formulas: IndexedVec<FunId, IndexedVec<BasisElemId, Formula>>) -> Self {
         let generators =
-            formulas.iter().map(|_| Rc::new(|_| Formula::TRUE) as Rc<dyn Fn(_) -> _>).collect();
+            formulas.iter().map(|_| Shared::new(|_| Formula::TRUE) as FormulaGenerator).collect();
+        let table_lens = formulas.iter().map(|formulas| Some(formulas.len())).collect();
         let cache = formulas
             .into_iter()
-            .map(|formulas| formulas.into_enumerate().map(|(i, f)| (i, Rc::new(f))).collect())
-            .map(RefCell::new)
+            .map(|formulas| formulas.into_enumerate().map(|(i, f)| (i, Shared::new(f))).collect())
+            .map(Lock::new)
             .collect();
 
-        Self { generators, cache }
+        Self { generators, cache, table_lens, stats: Lock::default() }
+    }
+
+    pub fn with_generators(generators: IndexedVec<FunId, FormulaGenerator>) -> Self {
+        let cache = generators.iter().map(|_| Lock::default()).collect();
+        let table_lens = generators.iter().map(|_| None).collect();
+        Self { generators, cache, table_lens, stats: Lock::default() }
     }
 
-    pub fn with_generators(
-        generators: IndexedVec<FunId, Rc<dyn Fn(BasisElemId) -> Formula>>,
-    ) -> Self {
-        let cache = generators.iter().map(|_| RefCell::new(Map::default())).collect();
-        Self { generators, cache }
+    pub fn get(&self, b: BasisElemId, f: FunId) -> Shared<Formula> {
+        let mut cache = self.cache[f].lock();
+        let entry = cache.entry(b);
+        CacheStats::record(&self.stats, matches!(entry, Entry::Occupied(_)));
+        entry.or_insert_with(|| Shared::new((self.generators[f])(b))).clone()
     }
 
-    pub fn get(&self, b: BasisElemId, f: FunId) -> Rc<Formula> {
-        self.cache[f]
-            .borrow_mut()
-            .entry(b)
-            .or_insert_with(|| Rc::new((self.generators[f])(b)))
-            .clone()
+    pub fn cache_stats(&self) -> CacheStats {
+        *self.stats.lock()
+    }
+
+    pub fn fun_count(&self) -> usize {
+        self.generators.len()
+    }
+
+    /// The number of formulas given for `f` to [`FunsFormulas::new`], or `None` if they are
+    /// computed by a generator. Basis elements past the end of the table have formula TRUE.
+    pub fn table_len(&self, f: FunId) -> Option<usize> {
+        self.table_lens[f]
     }
 }
 
+/// The formulas of the equations, composed with the formulas of the functions they use for
+/// each basis element. They are stored in a DAG shared by all of them, which is only locked
+/// while composing a formula that is not cached yet: the formulas are read without it.
 #[derive(Clone)]
 pub struct EqsFormulas {
     eqs: IndexedVec<VarId, FixEq>,
-    cache: IndexedVec<VarId, RefCell<Map<BasisElemId, Rc<Formula>>>>,
-    funs: Rc<FunsFormulas>,
+    priorities: Priorities,
+    cache: IndexedVec<VarId, Lock<Map<BasisElemId, FormulaRef>>>,
+    dag: Lock<FormulaDag>,
+    funs: Shared<FunsFormulas>,
+    stats: Lock<CacheStats>,
+    moves_repr: MovesRepr,
 }
 
 impl EqsFormulas {
-    pub fn new(eqs: IndexedVec<VarId, FixEq>, funs: Rc<FunsFormulas>) -> Self {
-        let cache = eqs.iter().map(|_| RefCell::new(Map::default())).collect();
-        Self { eqs, cache, funs }
+    pub fn new(eqs: IndexedVec<VarId, FixEq>, funs: Shared<FunsFormulas>) -> Self {
+        let cache = eqs.iter().map(|_| Lock::default()).collect();
+        let priorities = Priorities::new(&eqs);
+        let dag = Lock::default();
+        let (stats, moves_repr) = (Lock::default(), MovesRepr::default());
+        Self { eqs, priorities, cache, dag, funs, stats, moves_repr }
     }
 
-    pub(super) fn get(&self, b: BasisElemId, i: VarId) -> Rc<Formula> {
-        self.cache[i]
-            .borrow_mut()
-            .entry(b)
-            .or_insert_with(|| Rc::new(compose_moves(&self.eqs[i].expr, b, &self.funs)))
-            .clone()
+    /// Sets how the moves of player 0 are enumerated from the formulas.
+    pub fn set_moves_repr(&mut self, repr: MovesRepr) {
+        self.moves_repr = repr;
+    }
+
+    pub fn moves_repr(&self) -> MovesRepr {
+        self.moves_repr
+    }
+
+    /// The formula of the variable `i` for the basis element `b`.
+    pub(crate) fn get(&self, b: BasisElemId, i: VarId) -> FormulaRef {
+        let cached = self.cache[i].lock().get(&b).cloned();
+        CacheStats::record(&self.stats, cached.is_some());
+        if let Some(f) = cached {
+            return f;
+        }
+
+        // The cache is released while composing, so the only locks taken with the DAG are the
+        // ones of the functions' caches. If another thread composed the same formula in the
+        // meantime both get the same node.
+        let f = compose_moves(&self.eqs[i].expr, b, &self.funs, &mut self.dag.lock());
+        self.cache[i].lock().entry(b).or_insert(f).clone()
+    }
+
+    /// Number of distinct nodes in the formulas computed so far.
+    pub fn formula_nodes(&self) -> usize {
+        self.dag.lock().node_count()
+    }
+
+    /// Statistics of both the equations and the functions caches.
+    pub fn cache_stats(&self) -> CacheStats {
+        let (eqs, funs) = (*self.stats.lock(), self.funs.cache_stats());
+        CacheStats { hits: eqs.hits + funs.hits, misses: eqs.misses + funs.misses }
     }
 
     pub fn eq_fix_type(&self, i: VarId) -> FixType {
@@ -70,21 +148,83 @@ impl EqsFormulas {
     pub fn var_count(&self) -> usize {
         self.eqs.len()
     }
+
+    /// Compact priorities for the positions of each variable.
+    pub fn priorities(&self) -> &Priorities {
+        &self.priorities
+    }
 }
 
-fn compose_moves(expr: &Expr, b: BasisElemId, moves: &FunsFormulas) -> Formula {
+fn compose_moves(
+    expr: &Expr,
+    b: BasisElemId,
+    moves: &FunsFormulas,
+    dag: &mut FormulaDag,
+) -> FormulaRef {
     match expr {
-        Expr::Var(i) => Formula::Atom(b, *i),
-        Expr::And(exprs) => simplify_and(exprs.iter().map(|e| compose_moves(e, b, moves))),
-        Expr::Or(exprs) => simplify_or(exprs.iter().map(|e| compose_moves(e, b, moves))),
-        Expr::Fun(fun, args) => subst(&*moves.get(b, *fun).clone(), args, moves),
+        Expr::Var(i) => dag.atom(b, *i),
+        Expr::And(exprs) => {
+            let children =
+                exprs.iter().map(|e| compose_moves(e, b, moves, dag)).collect::<Vec<_>>();
+            dag.and(children)
+        }
+        Expr::Or(exprs) => {
+            let children =
+                exprs.iter().map(|e| compose_moves(e, b, moves, dag)).collect::<Vec<_>>();
+            dag.or(children)
+        }
+        Expr::Fun(fun, args) => subst(&moves.get(b, *fun), args, moves, dag),
     }
 }
 
-fn subst(formula: &Formula, args: &[Expr], moves: &FunsFormulas) -> Formula {
+fn subst(
+    formula: &Formula,
+    args: &[Expr],
+    moves: &FunsFormulas,
+    dag: &mut FormulaDag,
+) -> FormulaRef {
     match formula {
-        Formula::Atom(b, i) => compose_moves(&args[i.to_usize()], *b, moves),
-        Formula::And(fs) => simplify_and(fs.iter().map(|f| subst(f, args, moves))),
-        Formula::Or(fs) => simplify_or(fs.iter().map(|f| subst(f, args, moves))),
+        Formula::Atom(b, i) => compose_moves(&args[i.to_usize()], *b, moves, dag),
+        Formula::And(fs) => {
+            let children = fs.iter().map(|f| subst(f, args, moves, dag)).collect::<Vec<_>>();
+            dag.and(children)
+        }
+        Formula::Or(fs) => {
+            let children = fs.iter().map(|f| subst(f, args, moves, dag)).collect::<Vec<_>>();
+            dag.or(children)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EqsFormulas, FunsFormulas};
+    use crate::index::IndexedVec;
+    use crate::symbolic::eq::{Expr, FixEq, FixType, FunId, VarId};
+    use crate::symbolic::formula::{BasisElemId, Formula, Node};
+    use crate::sync::Shared;
+
+    #[test]
+    fn shared_subformulas() {
+        // X0 = f(X1) && X0, X1 = f(X1) && f(X0), where f(x) = x@0 || x@1 for every element
+        let call = |x| Expr::Fun(FunId(0), vec![Expr::Var(VarId(x))]);
+        let eqs = IndexedVec::from(vec![
+            FixEq { fix_type: FixType::Min, expr: Expr::And(vec![call(1), Expr::Var(VarId(0))]) },
+            FixEq { fix_type: FixType::Max, expr: Expr::And(vec![call(1), call(0)]) },
+        ]);
+        let atom = |b| Formula::Atom(BasisElemId(b), VarId(0));
+        let f = IndexedVec::from(vec![Formula::Or(vec![atom(0), atom(1)]); 2]);
+        let funs = FunsFormulas::new(IndexedVec::from(vec![f]));
+        let formulas = EqsFormulas::new(eqs, Shared::new(funs));
+
+        // Both variables share the formula of f(X1) for every basis element.
+        let [x0, x1] = [0, 1].map(|i| formulas.get(BasisElemId(0), VarId(i)));
+        assert_eq!(formulas.get(BasisElemId(1), VarId(1)), x1);
+        let Node::And(children) = x0.node() else { panic!("Expected a conjunction") };
+        let Node::And(conjuncts) = x1.node() else { panic!("Expected a conjunction") };
+        assert_eq!(children[0], conjuncts[0]);
+
+        // TRUE, FALSE, 4 atoms, 2 disjunctions for f and the conjunctions of X0 and X1.
+        assert_eq!(formulas.formula_nodes(), 10);
     }
 }
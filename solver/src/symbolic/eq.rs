@@ -3,7 +3,7 @@ use crate::index::new_index;
 new_index!(pub index VarId);
 new_index!(pub index FunId);
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Expr {
     Var(VarId),
     And(Vec<Expr>),
@@ -16,13 +16,13 @@ impl Expr {
     pub const TOP: Expr = Expr::And(Vec::new());
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum FixType {
     Min,
     Max,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct FixEq {
     pub fix_type: FixType,
     pub expr: Expr,
@@ -1,8 +1,18 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use indexmap::Equivalent;
+use rustc_hash::FxHashSet;
+
 use crate::index::new_index;
+use crate::sync::Shared;
+use crate::Set;
 
 use super::eq::VarId;
 
 new_index!(pub index BasisElemId);
+new_index!(pub index FormulaId);
 
 #[derive(Clone, Debug)]
 pub enum Formula {
@@ -16,48 +26,336 @@ impl Formula {
     pub const TRUE: Self = Formula::And(Vec::new());
 
     pub fn is_true(&self) -> bool {
-        match self {
-            Self::And(c) if c.is_empty() => true,
-            _ => false,
-        }
+        matches!(self, Self::And(c) if c.is_empty())
     }
 
     pub fn is_false(&self) -> bool {
-        match self {
-            Self::Or(c) if c.is_empty() => true,
+        matches!(self, Self::Or(c) if c.is_empty())
+    }
+}
+
+/// A formula stored in a [`FormulaDag`]. Nodes are immutable and shared, so they can be read
+/// without access to the DAG, and two formulas of the same DAG are equal only if they are the
+/// same node.
+#[derive(Clone)]
+pub struct FormulaRef(Shared<NodeData>);
+
+struct NodeData {
+    id: FormulaId,
+    node: Node,
+}
+
+/// A node of a `FormulaDag`, whose children are other nodes of the same DAG.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Atom(BasisElemId, VarId),
+    And(Box<[FormulaRef]>),
+    Or(Box<[FormulaRef]>),
+}
+
+impl FormulaRef {
+    /// The index of the node in its DAG.
+    pub fn id(&self) -> FormulaId {
+        self.0.id
+    }
+
+    pub fn node(&self) -> &Node {
+        &self.0.node
+    }
+
+    pub fn is_true(&self) -> bool {
+        matches!(self.node(), Node::And(c) if c.is_empty())
+    }
+
+    pub fn is_false(&self) -> bool {
+        matches!(self.node(), Node::Or(c) if c.is_empty())
+    }
+
+    // The children of the node if it has the operator `op`.
+    fn operands(&self, op: Op) -> Option<&[FormulaRef]> {
+        match (self.node(), op) {
+            (Node::And(children), Op::And) | (Node::Or(children), Op::Or) => Some(children),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for FormulaRef {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl Eq for FormulaRef {}
+
+impl Hash for FormulaRef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state)
+    }
+}
+
+impl PartialOrd for FormulaRef {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FormulaRef {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.id().cmp(&other.id())
+    }
+}
+
+impl fmt::Debug for FormulaRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.id().fmt(f)
+    }
+}
+
+// Borrowed form of a node, used to look up nodes without allocating their children.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum NodeKey<'a> {
+    Atom(BasisElemId, VarId),
+    And(&'a [FormulaRef]),
+    Or(&'a [FormulaRef]),
+}
+
+impl Node {
+    fn key(&self) -> NodeKey<'_> {
+        match *self {
+            Node::Atom(b, i) => NodeKey::Atom(b, i),
+            Node::And(ref children) => NodeKey::And(children),
+            Node::Or(ref children) => NodeKey::Or(children),
+        }
+    }
+}
+
+// A node of the DAG, hashed and compared by its content to find the existing copy of a node.
+#[derive(Clone)]
+struct Interned(FormulaRef);
+
+impl Hash for Interned {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.node().key().hash(state)
+    }
+}
+
+impl PartialEq for Interned {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.node().key() == other.0.node().key()
+    }
+}
+
+impl Eq for Interned {}
+
+impl Equivalent<Interned> for NodeKey<'_> {
+    fn equivalent(&self, interned: &Interned) -> bool {
+        *self == interned.0.node().key()
+    }
+}
+
+/// Hash-consed formulas, where identical subformulas are stored once and shared by all the
+/// formulas containing them. The DAG is only needed to build formulas: reading them only
+/// needs their `FormulaRef`.
+#[derive(Clone)]
+pub struct FormulaDag {
+    nodes: Set<Interned>,
+}
+
+impl FormulaDag {
+    pub fn new() -> Self {
+        let mut dag = Self { nodes: Set::default() };
+        dag.intern(NodeKey::And(&[]));
+        dag.intern(NodeKey::Or(&[]));
+        dag
+    }
+
+    /// The formula without children that is always true.
+    pub fn top(&self) -> FormulaRef {
+        self.nodes[0].0.clone()
+    }
+
+    /// The formula without children that is always false.
+    pub fn bot(&self) -> FormulaRef {
+        self.nodes[1].0.clone()
+    }
+
+    /// Number of distinct nodes in the DAG.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn atom(&mut self, b: BasisElemId, i: VarId) -> FormulaRef {
+        self.intern(NodeKey::Atom(b, i))
+    }
+
+    /// The conjunction of `children`, without the ones that are redundant.
+    pub fn and(&mut self, children: impl IntoIterator<Item = FormulaRef>) -> FormulaRef {
+        self.simplify(Op::And, children)
+    }
+
+    /// The disjunction of `children`, without the ones that are redundant.
+    pub fn or(&mut self, children: impl IntoIterator<Item = FormulaRef>) -> FormulaRef {
+        self.simplify(Op::Or, children)
+    }
+
+    /// Adds a formula with the same structure as `formula`, without simplifying it.
+    #[cfg(test)]
+    pub fn insert(&mut self, formula: &Formula) -> FormulaRef {
+        match formula {
+            &Formula::Atom(b, i) => self.atom(b, i),
+            Formula::And(children) => {
+                let children = children.iter().map(|f| self.insert(f)).collect();
+                self.intern_op(Op::And, children)
+            }
+            Formula::Or(children) => {
+                let children = children.iter().map(|f| self.insert(f)).collect();
+                self.intern_op(Op::Or, children)
+            }
+        }
+    }
+
+    // Builds the node combining `children` with `op`, after:
+    // - removing the neutral constant, and returning the absorbing one if present;
+    // - flattening the children with the same operator and removing duplicates;
+    // - removing the children of the dual operator implied by the others, either because
+    //   they contain another child, as in `a & (a | b) = a`, or because they contain all the
+    //   children of another one, as in `(a | b) & (a | b | c) = a | b`.
+    fn simplify(&mut self, op: Op, children: impl IntoIterator<Item = FormulaRef>) -> FormulaRef {
+        let (neutral, absorbing) = match op {
+            Op::And => (self.top(), self.bot()),
+            Op::Or => (self.bot(), self.top()),
+        };
+
+        let mut seen = FxHashSet::default();
+        let mut flat = Vec::new();
+        for f in children {
+            if f == absorbing {
+                return absorbing;
+            }
+            match f.operands(op) {
+                Some(operands) => {
+                    flat.extend(operands.iter().filter(|f| seen.insert(f.id())).cloned())
+                }
+                None if seen.insert(f.id()) => flat.push(f),
+                None => {}
+            }
+        }
+
+        // The operands of the children with the dual operator.
+        let dual = flat
+            .iter()
+            .map(|f| f.operands(op.dual()).map(|fs| fs.iter().map(FormulaRef::id).collect()))
+            .collect::<Vec<Option<FxHashSet<_>>>>();
+        let implied = |f: &FormulaRef| {
+            seen.contains(&f.id())
+                || f.operands(op).is_some_and(|fs| fs.iter().all(|f| seen.contains(&f.id())))
+        };
+        // Children with the same operands are only subsumed by the first of them.
+        let subsumes = |i: usize, j: usize| match (&dual[i], &dual[j]) {
+            (Some(fs), Some(gs)) => i != j && fs.is_subset(gs) && (fs.len() < gs.len() || i < j),
             _ => false,
+        };
+        let mut kept = (0..flat.len())
+            .filter(|&j| {
+                let Some(operands) = flat[j].operands(op.dual()) else { return true };
+                !operands.iter().any(implied) && !(0..flat.len()).any(|i| subsumes(i, j))
+            })
+            .map(|j| flat[j].clone())
+            .collect::<Vec<_>>();
+
+        match kept.len() {
+            0 => neutral,
+            1 => kept.pop().unwrap(),
+            _ => self.intern_op(op, kept),
+        }
+    }
+
+    // Children are sorted, so that nodes differing only in their order are shared.
+    fn intern_op(&mut self, op: Op, mut children: Vec<FormulaRef>) -> FormulaRef {
+        children.sort_unstable();
+        self.intern(match op {
+            Op::And => NodeKey::And(&children),
+            Op::Or => NodeKey::Or(&children),
+        })
+    }
+
+    fn intern(&mut self, key: NodeKey) -> FormulaRef {
+        if let Some(Interned(f)) = self.nodes.get(&key) {
+            return f.clone();
         }
+
+        let node = match key {
+            NodeKey::Atom(b, i) => Node::Atom(b, i),
+            NodeKey::And(children) => Node::And(children.into()),
+            NodeKey::Or(children) => Node::Or(children.into()),
+        };
+        let f = FormulaRef(Shared::new(NodeData { id: FormulaId(self.nodes.len()), node }));
+        self.nodes.insert(Interned(f.clone()));
+        f
     }
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    And,
+    Or,
+}
 
-    pub fn simplify(self) -> Self {
+impl Op {
+    fn dual(self) -> Op {
         match self {
-            Formula::Atom(b, i) => Formula::Atom(b, i),
-            Formula::And(children) => simplify_and(children.into_iter().map(Self::simplify)),
-            Formula::Or(children) => simplify_or(children.into_iter().map(Self::simplify)),
+            Op::And => Op::Or,
+            Op::Or => Op::And,
         }
     }
 }
 
-pub fn simplify_and(iter: impl Iterator<Item = Formula>) -> Formula {
-    let children = iter
-        .filter(|f| !f.is_true())
-        .map(|f| (!f.is_false()).then_some(f))
-        .collect::<Option<Vec<_>>>();
-    match children {
-        None => Formula::FALSE,
-        Some(children) if children.len() == 1 => children.into_iter().next().unwrap(),
-        Some(children) => Formula::And(children),
+impl Default for FormulaDag {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-pub fn simplify_or(iter: impl Iterator<Item = Formula>) -> Formula {
-    let children = iter
-        .filter(|f| !f.is_false())
-        .map(|f| (!f.is_true()).then_some(f))
-        .collect::<Option<Vec<_>>>();
-    match children {
-        None => Formula::TRUE,
-        Some(children) if children.len() == 1 => children.into_iter().next().unwrap(),
-        Some(children) => Formula::Or(children),
+#[cfg(test)]
+mod tests {
+    use super::{BasisElemId, FormulaDag, FormulaRef, Node, VarId};
+
+    fn atoms<const N: usize>(dag: &mut FormulaDag) -> [FormulaRef; N] {
+        std::array::from_fn(|i| dag.atom(BasisElemId(i), VarId(i)))
+    }
+
+    #[test]
+    fn flatten_and_deduplicate() {
+        let mut dag = FormulaDag::new();
+        let [a, b, c] = atoms(&mut dag);
+        let top = dag.top();
+
+        let ab = dag.and([b.clone(), a.clone()]);
+        let f = dag.and([a.clone(), top.clone(), ab, c.clone(), b.clone()]);
+        assert_eq!(f.node(), &Node::And(Box::new([a.clone(), b, c])));
+        assert_eq!(dag.or([f, top.clone()]), top);
+        assert_eq!(dag.or([a.clone()]), a);
+    }
+
+    #[test]
+    fn absorption() {
+        let mut dag = FormulaDag::new();
+        let [a, b, c] = atoms(&mut dag);
+
+        // a & (a | b) = a and a | (a & b) = a
+        let a_or_b = dag.or([a.clone(), b.clone()]);
+        assert_eq!(dag.and([a.clone(), a_or_b.clone()]), a);
+        let a_and_b = dag.and([a.clone(), b.clone()]);
+        assert_eq!(dag.or([a_and_b.clone(), a.clone()]), a);
+
+        // a & b & (c | (a & b)) = a & b
+        let implied = dag.or([c.clone(), a_and_b.clone()]);
+        assert_eq!(dag.and([a.clone(), implied, b.clone()]), a_and_b);
+
+        // (a | b) & (a | b | c) = a | b, also when the operands are in a different order.
+        let a_or_b_or_c = dag.or([c, b.clone(), a.clone()]);
+        assert_eq!(dag.and([a_or_b_or_c, a_or_b.clone()]), a_or_b);
+        assert_eq!(dag.or([b, a]), a_or_b);
     }
 }
@@ -1,5 +1,9 @@
+pub mod analysis;
+pub mod bdd;
 pub mod compose;
 pub mod eq;
 pub mod formula;
 pub mod moves;
 pub mod normalize;
+pub mod simplify;
+pub mod validate;
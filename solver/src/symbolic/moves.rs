@@ -1,10 +1,11 @@
-use std::rc::Rc;
-
 use crate::retain::{simplify, Simplify};
+use crate::sync::Shared;
+use crate::Map;
 
+use super::bdd::BddMoves;
 use super::compose::EqsFormulas;
 use super::eq::VarId;
-use super::formula::{BasisElemId, Formula};
+use super::formula::{BasisElemId, FormulaId, FormulaRef, Node};
 
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub struct P0Pos {
@@ -14,19 +15,35 @@ pub struct P0Pos {
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct P1Pos {
-    pub moves: Rc<[P0Pos]>,
+    pub moves: Shared<[P0Pos]>,
 }
 
 pub struct P0Moves {
     exhausted: bool,
-    inner: FormulaIter,
+    inner: MovesIter,
+}
+
+/// How the moves of player 0 are enumerated from the formula of its position.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MovesRepr {
+    /// Walk the formula directly, giving the terms of its DNF.
+    #[default]
+    Formula,
+    /// Build a BDD of the formula and give its minimal satisfying sets. This avoids the
+    /// DNF blowup of formulas with many disjunctions over the same positions.
+    Bdd,
+}
+
+enum MovesIter {
+    Formula(FormulaIter),
+    Bdd(BddMoves),
 }
 
-pub struct P1Moves(Rc<[P0Pos]>, usize);
+pub struct P1Moves(Shared<[P0Pos]>, usize);
 
 impl P0Pos {
     pub fn moves(&self, formulas: &EqsFormulas) -> P0Moves {
-        P0Moves::from_formula(&formulas.get(self.b, self.i))
+        P0Moves::from_formula(&formulas.get(self.b, self.i), formulas.moves_repr())
     }
 }
 
@@ -37,17 +54,31 @@ impl P1Pos {
 }
 
 impl P0Moves {
-    fn from_formula(formula: &Formula) -> Self {
-        let inner = FormulaIter::new(formula);
-        let exhausted = inner.is_false();
+    fn from_formula(f: &FormulaRef, repr: MovesRepr) -> Self {
+        let (exhausted, inner) = match repr {
+            MovesRepr::Formula => {
+                let inner = FormulaIter::new(f);
+                (inner.is_false(), MovesIter::Formula(inner))
+            }
+            MovesRepr::Bdd => {
+                let inner = BddMoves::new(f);
+                (inner.is_exhausted(), MovesIter::Bdd(inner))
+            }
+        };
         P0Moves { exhausted, inner }
     }
 
     pub fn simplify(&mut self, mut assumption: impl FnMut(P0Pos) -> Assumption) {
-        match self.inner.simplify(false, &mut assumption) {
-            (Assumption::Win, _) => self.inner = FormulaIter::And(Vec::new()),
-            (Assumption::Lose, _) | (_, Status::Reset) => self.exhausted = true,
-            _ => {}
+        match &mut self.inner {
+            MovesIter::Formula(inner) => match inner.simplify(false, &mut assumption) {
+                (Assumption::Win, _) => *inner = FormulaIter::And(Vec::new()),
+                (Assumption::Lose, _) | (_, Status::Reset) => self.exhausted = true,
+                _ => {}
+            },
+            MovesIter::Bdd(inner) => {
+                inner.simplify(assumption);
+                self.exhausted |= inner.is_exhausted();
+            }
         }
     }
 
@@ -71,11 +102,18 @@ impl Iterator for P0Moves {
             return None;
         }
 
-        let moves = self.inner.current();
-
-        if !self.inner.advance() {
-            self.exhausted = true;
-        }
+        let moves = match &mut self.inner {
+            MovesIter::Formula(inner) => {
+                let moves = inner.current();
+                self.exhausted = !inner.advance();
+                moves
+            }
+            MovesIter::Bdd(inner) => {
+                let moves = inner.next()?;
+                self.exhausted = inner.is_exhausted();
+                moves
+            }
+        };
 
         Some(P1Pos { moves })
     }
@@ -94,7 +132,7 @@ impl Iterator for P1Moves {
 
 impl Default for P1Moves {
     fn default() -> Self {
-        Self(Rc::new([]), 0)
+        Self(Shared::new([]), 0)
     }
 }
 
@@ -104,6 +142,8 @@ enum FormulaIter {
     And(Vec<FormulaIter>),
     // Contains iterators for subformulas and the currently active subformula.
     Or(Vec<FormulaIter>, usize),
+    // A subformula of a disjunction that was never active, expanded when it becomes active.
+    Pending(FormulaRef),
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -121,11 +161,21 @@ enum Status {
 }
 
 impl FormulaIter {
-    fn new(f: &Formula) -> Self {
-        match *f {
-            Formula::Atom(b, i) => Self::Atom(P0Pos { b, i }),
-            Formula::And(ref children) => Self::And(children.iter().map(Self::new).collect()),
-            Formula::Or(ref children) => Self::Or(children.iter().map(Self::new).collect(), 0),
+    fn new(f: &FormulaRef) -> Self {
+        match *f.node() {
+            Node::Atom(b, i) => Self::Atom(P0Pos { b, i }),
+            Node::And(ref children) => Self::And(children.iter().map(Self::new).collect()),
+            Node::Or(ref children) => {
+                let pending = children.iter().skip(1).cloned().map(Self::Pending);
+                Self::Or(children.first().map(Self::new).into_iter().chain(pending).collect(), 0)
+            }
+        }
+    }
+
+    // Expands the iterator if it is pending, before it becomes active.
+    fn activate(&mut self) {
+        if let FormulaIter::Pending(f) = self {
+            *self = Self::new(&f.clone());
         }
     }
 
@@ -141,15 +191,18 @@ impl FormulaIter {
     ) -> (Assumption, Status) {
         match self {
             FormulaIter::Atom(p) => (assumption(*p), Status::Still),
+            // Not being active it has nothing to advance, and it's kept unexpanded unless it
+            // can be removed.
+            FormulaIter::Pending(f) => (assume(f, assumption, &mut Map::default()), Status::Still),
             FormulaIter::And(iters) => {
-                let mut adv = reset.then(|| 0);
+                let mut adv = reset.then_some(0);
 
                 let cleared = simplify(iters, |_, new_i, iter| {
                     let (assumption, status) = iter.simplify(reset, assumption);
                     match status {
                         Status::Still => {}
                         Status::Step => reset = true,
-                        Status::Reset => adv = adv.or((!reset).then(|| new_i)),
+                        Status::Reset => adv = adv.or((!reset).then_some(new_i)),
                     }
                     reset |= adv.is_some();
                     match assumption {
@@ -226,6 +279,7 @@ impl FormulaIter {
                     Status::Still => (new_pos, Status::Still),
                 };
                 *pos = new_pos;
+                iters[new_pos].activate();
 
                 if iters.len() == 1 {
                     *self = iters.pop().unwrap();
@@ -236,12 +290,13 @@ impl FormulaIter {
         }
     }
 
-    fn current(&self) -> Rc<[P0Pos]> {
+    fn current(&self) -> Shared<[P0Pos]> {
         fn inner(iter: &FormulaIter, out: &mut Vec<P0Pos>) {
             match *iter {
                 FormulaIter::Atom(p) => out.push(p),
                 FormulaIter::And(ref iters) => iters.iter().for_each(|iter| inner(iter, out)),
                 FormulaIter::Or(ref iters, pos) => inner(&iters[pos], out),
+                FormulaIter::Pending(_) => unreachable!("Pending subformula is not active"),
             }
         }
 
@@ -274,20 +329,53 @@ impl FormulaIter {
                     _ => (0, false),
                 };
                 *pos = new_pos;
+                iters[new_pos].activate();
                 advanced
             }
+            FormulaIter::Pending(_) => unreachable!("Pending subformula is not active"),
+        }
+    }
+}
+
+// The assumption for the whole formula `f`, given the ones for its positions.
+fn assume(
+    f: &FormulaRef,
+    assumption: &mut impl FnMut(P0Pos) -> Assumption,
+    memo: &mut Map<FormulaId, Assumption>,
+) -> Assumption {
+    let (children, absorbing, neutral) = match *f.node() {
+        Node::Atom(b, i) => return assumption(P0Pos { b, i }),
+        Node::And(ref children) => (children, Assumption::Lose, Assumption::Win),
+        Node::Or(ref children) => (children, Assumption::Win, Assumption::Lose),
+    };
+    if let Some(&result) = memo.get(&f.id()) {
+        return result;
+    }
+
+    let mut result = neutral;
+    for child in children.iter() {
+        match assume(child, assumption, memo) {
+            a if a == absorbing => {
+                result = absorbing;
+                break;
+            }
+            Assumption::Unknown => result = Assumption::Unknown,
+            _ => {}
         }
     }
+    memo.insert(f.id(), result);
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
 
-    use super::{Assumption, BasisElemId, Formula, VarId};
+    use super::{Assumption, BasisElemId, VarId};
     use crate::index::AsIndex;
     use crate::retain::Simplify;
-    use crate::symbolic::moves::{P0Moves, P1Pos};
+    use crate::symbolic::formula::{Formula, FormulaDag};
+    use crate::symbolic::moves::{MovesRepr, P0Moves, P1Pos};
 
     macro_rules! formula {
         ($i:literal) => { Formula::Atom(BasisElemId($i), VarId($i)) };
@@ -356,20 +444,25 @@ mod tests {
                 #[test]
                 fn $name() {
                     let f = formula!($($f)*);
-                    let mut moves = P0Moves::from_formula(&f);
-                    let mut out = Vec::new();
+                    let mut dag = FormulaDag::new();
+                    let formula = dag.insert(&f);
+
+                    for repr in [MovesRepr::Formula, MovesRepr::Bdd] {
+                        let mut moves = P0Moves::from_formula(&formula, repr);
+                        let mut out = Vec::new();
 
-                    use std::collections::HashSet;
-                    let mut winning = HashSet::<usize>::new();
-                    let mut losing = HashSet::<usize>::new();
+                        use std::collections::HashSet;
+                        let mut winning = HashSet::<usize>::new();
+                        let mut losing = HashSet::<usize>::new();
 
-                    test_formula!(@STMT(moves out winning losing) $($stmts)*);
+                        test_formula!(@STMT(moves out winning losing) $($stmts)*);
 
-                    check_moves(&f, &out, |b| match () {
-                        _ if winning.contains(&b) => Assumption::Win,
-                        _ if losing.contains(&b) => Assumption::Lose,
-                        _ => Assumption::Unknown,
-                    });
+                        check_moves(&f, &out, |b| match () {
+                            _ if winning.contains(&b) => Assumption::Win,
+                            _ if losing.contains(&b) => Assumption::Lose,
+                            _ => Assumption::Unknown,
+                        });
+                    }
                 }
             )*
         };
@@ -465,5 +558,10 @@ mod tests {
             simplify(win 2),
             rest
         ),
+        simplify_pending(
+            f = [ 0 | (1 & 2) | 3 ],
+            simplify(lose 1),
+            rest
+        ),
     }
 }
@@ -0,0 +1,398 @@
+use crate::index::IndexedVec;
+use crate::Map;
+
+use super::analysis::Dependencies;
+use super::eq::{Expr, FixEq, FixType, VarId};
+
+/// For each variable of a system, the variable of the simplified system with the same
+/// solution, or `None` if it was removed without one.
+pub type VarMap = IndexedVec<VarId, Option<VarId>>;
+
+/// Runs all the simplifications, keeping a variable with the solution of `root`.
+pub fn simplify_sys(
+    eqs: &IndexedVec<VarId, FixEq>,
+    root: VarId,
+) -> (IndexedVec<VarId, FixEq>, VarMap) {
+    let (eqs, map) = propagate_constants(eqs);
+    let (eqs, next) = remove_unreachable(&eqs, map[root].unwrap());
+    let map = compose_maps(&map, &next);
+    let (eqs, next) = merge_identical(&eqs);
+    let map = compose_maps(&map, &next);
+    let (eqs, next) = inline_single_uses(&eqs, map[root].unwrap());
+    (eqs, compose_maps(&map, &next))
+}
+
+/// The map obtained by following `first` and then `second`.
+pub fn compose_maps(first: &VarMap, second: &VarMap) -> VarMap {
+    first.iter().map(|var| var.and_then(|var| second[var])).collect()
+}
+
+/// Replaces the variables whose equation is equivalent to `true` or `false` with that
+/// constant, and removes the constants from conjunctions and disjunctions. The equations of
+/// the constant variables are kept, so every variable keeps its index.
+pub fn propagate_constants(eqs: &IndexedVec<VarId, FixEq>) -> (IndexedVec<VarId, FixEq>, VarMap) {
+    let deps = Dependencies::new(eqs);
+    let mut users = IndexedVec::from(vec![Vec::new(); eqs.len()]);
+    for (var, deps) in deps.deps.enumerate() {
+        deps.iter().for_each(|&dep| users[dep].push(var));
+    }
+
+    // Variables can only become constant when one of the variables they use does.
+    let mut consts = IndexedVec::from(vec![None; eqs.len()]);
+    let mut queue = eqs.indexes().collect::<Vec<_>>();
+    while let Some(var) = queue.pop() {
+        if consts[var].is_some() {
+            continue;
+        }
+        if let Some(value) = constant_of(&propagate(&eqs[var].expr, &consts)) {
+            consts[var] = Some(value);
+            queue.extend(users[var].iter().copied());
+        }
+    }
+
+    let eqs = eqs
+        .iter()
+        .map(|eq| FixEq { fix_type: eq.fix_type, expr: propagate(&eq.expr, &consts) })
+        .collect();
+    let map = (0..consts.len()).map(|var| Some(VarId(var))).collect();
+    (eqs, map)
+}
+
+/// Removes the variables that the solution of `root` does not depend on.
+pub fn remove_unreachable(
+    eqs: &IndexedVec<VarId, FixEq>,
+    root: VarId,
+) -> (IndexedVec<VarId, FixEq>, VarMap) {
+    let deps = Dependencies::new(eqs);
+    let mut reachable = IndexedVec::from(vec![false; eqs.len()]);
+    let mut queue = vec![root];
+    while let Some(var) = queue.pop() {
+        if !std::mem::replace(&mut reachable[var], true) {
+            queue.extend(deps.deps[var].iter().copied());
+        }
+    }
+
+    let actions = reachable
+        .iter()
+        .map(|&reachable| if reachable { Action::Keep } else { Action::Remove })
+        .collect();
+    rebuild(eqs, &actions)
+}
+
+/// Replaces the variables with the same fixpoint type and expression with the outermost of
+/// them, as long as this doesn't change which player wins the cycles through them.
+pub fn merge_identical(eqs: &IndexedVec<VarId, FixEq>) -> (IndexedVec<VarId, FixEq>, VarMap) {
+    let alternations = Alternations::new(eqs);
+
+    let mut outermost = Map::<(FixType, &Expr), VarId>::default();
+    let actions = eqs
+        .enumerate()
+        .rev()
+        .map(|(var, eq)| {
+            let into = *outermost.entry((eq.fix_type, &eq.expr)).or_insert(var);
+            match into != var && alternations.can_merge(var, into) {
+                true => Action::Replace(Expr::Var(into)),
+                false => Action::Keep,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    rebuild(eqs, &actions.into_iter().rev().collect())
+}
+
+/// Replaces the variables used only once, except `root`, with their expression, as long as
+/// this doesn't change which player wins the cycles through them.
+pub fn inline_single_uses(
+    eqs: &IndexedVec<VarId, FixEq>,
+    root: VarId,
+) -> (IndexedVec<VarId, FixEq>, VarMap) {
+    let alternations = Alternations::new(eqs);
+
+    let mut uses = IndexedVec::from(vec![0; eqs.len()]);
+    let mut user = IndexedVec::from(vec![root; eqs.len()]);
+    for (var, eq) in eqs.enumerate() {
+        for_each_var(&eq.expr, &mut |used| {
+            uses[used] += 1;
+            user[used] = var;
+        });
+    }
+
+    let mut actions = IndexedVec::from(vec![Action::Keep; eqs.len()]);
+    for var in eqs.indexes() {
+        if var == root || uses[var] != 1 || !alternations.can_inline(var, user[var]) {
+            continue;
+        }
+
+        // The user could itself be inlined, and this must not form a cycle.
+        let mut target = user[var];
+        while target != var && matches!(actions[target], Action::Replace(_)) {
+            target = user[target];
+        }
+        if target != var {
+            actions[var] = Action::Replace(eqs[var].expr.clone());
+        }
+    }
+
+    rebuild(eqs, &actions)
+}
+
+#[derive(Clone)]
+enum Action {
+    Keep,
+    Remove,
+    /// Replace the variable with an expression over the variables of the original system.
+    Replace(Expr),
+}
+
+// Builds the system made of the kept equations, in the same order. Replaced variables are
+// mapped to the variable they are replaced with, if that's all their expression is.
+fn rebuild(
+    eqs: &IndexedVec<VarId, FixEq>,
+    actions: &IndexedVec<VarId, Action>,
+) -> (IndexedVec<VarId, FixEq>, VarMap) {
+    let mut kept = 0;
+    let mut map = actions
+        .iter()
+        .map(|action| {
+            let new = matches!(action, Action::Keep).then_some(VarId(kept));
+            kept += new.is_some() as usize;
+            new
+        })
+        .collect::<VarMap>();
+
+    fn rename(expr: &Expr, actions: &IndexedVec<VarId, Action>, map: &VarMap) -> Expr {
+        match expr {
+            Expr::Var(var) => match &actions[*var] {
+                Action::Keep => Expr::Var(map[*var].unwrap()),
+                Action::Remove => panic!("Removed variable {var:?} is still used"),
+                Action::Replace(expr) => rename(expr, actions, map),
+            },
+            Expr::And(children) => {
+                Expr::And(children.iter().map(|e| rename(e, actions, map)).collect())
+            }
+            Expr::Or(children) => {
+                Expr::Or(children.iter().map(|e| rename(e, actions, map)).collect())
+            }
+            Expr::Fun(fun, args) => {
+                Expr::Fun(*fun, args.iter().map(|e| rename(e, actions, map)).collect())
+            }
+        }
+    }
+
+    let new_eqs = eqs
+        .enumerate()
+        .filter(|(var, _)| matches!(actions[*var], Action::Keep))
+        .map(|(_, eq)| FixEq { fix_type: eq.fix_type, expr: rename(&eq.expr, actions, &map) })
+        .collect();
+
+    for (var, action) in actions.enumerate() {
+        if let Action::Replace(expr) = action {
+            if let Expr::Var(new) = rename(expr, actions, &map) {
+                map[var] = Some(new);
+            }
+        }
+    }
+
+    (new_eqs, map)
+}
+
+// The variables in cycles, and for each of them the closest variables of its component with
+// the other fixpoint type before and after it. Replacing a variable with another one in the
+// same component preserves the winner of the cycles through it when no such variable is
+// between them.
+struct Alternations {
+    cyclic: IndexedVec<VarId, bool>,
+    prev: IndexedVec<VarId, Option<VarId>>,
+    next: IndexedVec<VarId, Option<VarId>>,
+}
+
+impl Alternations {
+    fn new(eqs: &IndexedVec<VarId, FixEq>) -> Self {
+        let mut cyclic = IndexedVec::from(vec![false; eqs.len()]);
+        let mut prev = IndexedVec::from(vec![None; eqs.len()]);
+        let mut next = IndexedVec::from(vec![None; eqs.len()]);
+
+        let deps = Dependencies::new(eqs);
+        for scc in deps.sccs.iter().filter(|scc| scc.alternation_depth > 0) {
+            scc.vars.iter().for_each(|&var| cyclic[var] = true);
+            last_alternation(eqs, scc.vars.iter().copied(), &mut prev);
+            last_alternation(eqs, scc.vars.iter().copied().rev(), &mut next);
+        }
+
+        Self { cyclic, prev, next }
+    }
+
+    /// Whether `var`, used only by `user`, can be replaced by its expression. Every cycle
+    /// through `var` also goes through `user`, so `var` only matters if it's the outermost
+    /// variable in it, and the next outermost must then have the same fixpoint type.
+    fn can_inline(&self, var: VarId, user: VarId) -> bool {
+        !self.cyclic[var] || user > var || self.prev[var].is_none_or(|prev| prev < user)
+    }
+
+    /// Whether `var` can be replaced by `into`, an outer variable with the same equation.
+    /// Cycles through `var` then go through `into` instead, which must not change the
+    /// fixpoint type of the outermost variable in them.
+    fn can_merge(&self, var: VarId, into: VarId) -> bool {
+        !self.cyclic[var] || self.next[var].is_none_or(|next| next > into)
+    }
+}
+
+// Sets for each of `vars` the last of the previous ones with the other fixpoint type.
+fn last_alternation(
+    eqs: &IndexedVec<VarId, FixEq>,
+    vars: impl Iterator<Item = VarId>,
+    out: &mut IndexedVec<VarId, Option<VarId>>,
+) {
+    let (mut last_min, mut last_max) = (None, None);
+    for var in vars {
+        match eqs[var].fix_type {
+            FixType::Min => (out[var], last_min) = (last_max, Some(var)),
+            FixType::Max => (out[var], last_max) = (last_min, Some(var)),
+        }
+    }
+}
+
+// Replaces the variables with their known constant value, removing the constants that don't
+// change the value of conjunctions and disjunctions.
+fn propagate(expr: &Expr, consts: &IndexedVec<VarId, Option<bool>>) -> Expr {
+    let propagate_many = |children: &[Expr], absorbing: bool| {
+        let mut out = Vec::new();
+        for child in children.iter().map(|child| propagate(child, consts)) {
+            match constant_of(&child) {
+                Some(value) if value == absorbing => return None,
+                Some(_) => {}
+                None => out.push(child),
+            }
+        }
+        Some(out)
+    };
+
+    match expr {
+        Expr::Var(var) => match consts[*var] {
+            Some(true) => Expr::TOP,
+            Some(false) => Expr::BOT,
+            None => Expr::Var(*var),
+        },
+        Expr::And(children) => match propagate_many(children, false) {
+            None => Expr::BOT,
+            Some(mut children) if children.len() == 1 => children.pop().unwrap(),
+            Some(children) => Expr::And(children),
+        },
+        Expr::Or(children) => match propagate_many(children, true) {
+            None => Expr::TOP,
+            Some(mut children) if children.len() == 1 => children.pop().unwrap(),
+            Some(children) => Expr::Or(children),
+        },
+        Expr::Fun(fun, args) => {
+            Expr::Fun(*fun, args.iter().map(|arg| propagate(arg, consts)).collect())
+        }
+    }
+}
+
+fn constant_of(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::And(children) if children.is_empty() => Some(true),
+        Expr::Or(children) if children.is_empty() => Some(false),
+        _ => None,
+    }
+}
+
+fn for_each_var(expr: &Expr, f: &mut impl FnMut(VarId)) {
+    match expr {
+        Expr::Var(var) => f(*var),
+        Expr::And(children) | Expr::Or(children) | Expr::Fun(_, children) => {
+            children.iter().for_each(|child| for_each_var(child, f))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{inline_single_uses, merge_identical, simplify_sys};
+    use crate::index::IndexedVec;
+    use crate::symbolic::eq::{Expr, FixEq, FixType, FunId, VarId};
+
+    fn var(i: usize) -> Expr {
+        Expr::Var(VarId(i))
+    }
+
+    fn eqs(eqs: Vec<(FixType, Expr)>) -> IndexedVec<VarId, FixEq> {
+        eqs.into_iter().map(|(fix_type, expr)| FixEq { fix_type, expr }).collect()
+    }
+
+    fn map(vars: impl IntoIterator<Item = Option<usize>>) -> Vec<Option<VarId>> {
+        vars.into_iter().map(|var| var.map(VarId)).collect()
+    }
+
+    #[test]
+    fn constants_and_unreachable() {
+        // X0 = true, X1 = X0 && f(X2 || X0), X2 = X2, X3 = X1 || X2
+        let (eqs, vars) = simplify_sys(
+            &eqs(vec![
+                (FixType::Min, Expr::TOP),
+                (
+                    FixType::Max,
+                    Expr::And(vec![
+                        var(0),
+                        Expr::Fun(FunId(0), vec![Expr::Or(vec![var(2), var(0)])]),
+                    ]),
+                ),
+                (FixType::Max, var(2)),
+                (FixType::Min, Expr::Or(vec![var(1), var(2)])),
+            ]),
+            VarId(1),
+        );
+
+        // X1 = f(true) is all that remains.
+        assert_eq!(vars.to_vec(), map([None, Some(0), None, None]));
+        assert_eq!(eqs.len(), 1);
+        assert_eq!(eqs[VarId(0)].expr, Expr::Fun(FunId(0), vec![Expr::TOP]));
+    }
+
+    #[test]
+    fn merge_without_alternations() {
+        // X0 = X1 && X2, X1 = X0, X2 = X1 && X2, X3 = X1 && X2, X4 = X3
+        let system = |fix_type| {
+            eqs(vec![
+                (FixType::Max, Expr::And(vec![var(1), var(2)])),
+                (FixType::Max, var(0)),
+                (fix_type, Expr::And(vec![var(1), var(2)])),
+                (FixType::Max, Expr::And(vec![var(1), var(2)])),
+                (FixType::Max, var(3)),
+            ])
+        };
+
+        // X0 can only be merged into X3 if X2, which is between them in its cycle, has the
+        // same fixpoint type.
+        let (eqs, vars) = merge_identical(&system(FixType::Min));
+        assert_eq!(vars.to_vec(), map((0..5).map(Some)));
+        assert_eq!(eqs.len(), 5);
+
+        let (eqs, vars) = merge_identical(&system(FixType::Max));
+        assert_eq!(vars.to_vec(), map([Some(1), Some(0), Some(1), Some(1), Some(2)]));
+        assert_eq!(eqs[VarId(0)].expr, var(1));
+    }
+
+    #[test]
+    fn inline_without_alternations() {
+        // X0 = X1 && X3, X1 = X2, X2 = X0 || X1, X3 = X2
+        let system = |fix_type| {
+            eqs(vec![
+                (FixType::Min, Expr::And(vec![var(1), var(3)])),
+                (FixType::Min, var(2)),
+                (fix_type, Expr::Or(vec![var(0), var(1)])),
+                (FixType::Min, var(2)),
+            ])
+        };
+
+        // X0 is inlined in the outer X2. X3 is the outermost variable of the cycle through X2
+        // and X0, so it is only inlined if X2 has the same fixpoint type.
+        let (eqs, vars) = inline_single_uses(&system(FixType::Max), VarId(2));
+        assert_eq!(vars.to_vec(), map([None, Some(0), Some(1), Some(2)]));
+        assert_eq!(eqs[VarId(1)].expr, Expr::Or(vec![Expr::And(vec![var(0), var(2)]), var(0)]));
+
+        let (eqs, vars) = inline_single_uses(&system(FixType::Min), VarId(2));
+        assert_eq!(vars.to_vec(), map([None, Some(0), Some(1), Some(1)]));
+        assert_eq!(eqs[VarId(1)].expr, Expr::Or(vec![Expr::And(vec![var(0), var(1)]), var(0)]));
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+
+use crate::index::{AsIndex, IndexedVec};
+
+use super::compose::FunsFormulas;
+use super::eq::{Expr, FixEq, FunId, VarId};
+use super::formula::{BasisElemId, Formula};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The equation of `eq` refers to a variable without an equation.
+    UndefinedVar {
+        eq: VarId,
+        var: VarId,
+    },
+    /// The equation of `eq` calls a function without formulas.
+    UndefinedFun {
+        eq: VarId,
+        fun: FunId,
+    },
+    /// The equation of `eq` calls `fun` with `args` arguments, but the formula of `fun` for
+    /// the basis element `b` refers to the argument `arg`.
+    MissingArg {
+        eq: VarId,
+        fun: FunId,
+        args: usize,
+        b: BasisElemId,
+        arg: VarId,
+    },
+    /// The formula of `fun` for the basis element `b` refers to the basis element `elem`,
+    /// which is not less than the number of basis elements.
+    BasisElemOutOfRange {
+        fun: FunId,
+        b: BasisElemId,
+        elem: BasisElemId,
+    },
+    /// The table of formulas of `fun` has `len` entries instead of one for each basis element.
+    TableLength {
+        fun: FunId,
+        len: usize,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::UndefinedVar { eq, var } => {
+                write!(f, "equation {eq:?} refers to undefined variable {var:?}")
+            }
+            ValidationError::UndefinedFun { eq, fun } => {
+                write!(f, "equation {eq:?} calls undefined function {fun:?}")
+            }
+            ValidationError::MissingArg { eq, fun, args, b, arg } => write!(
+                f,
+                "equation {eq:?} calls {fun:?} with {args} arguments, \
+                 but its formula for {b:?} refers to argument {arg:?}"
+            ),
+            ValidationError::BasisElemOutOfRange { fun, b, elem } => {
+                write!(f, "formula of {fun:?} for {b:?} refers to out of range {elem:?}")
+            }
+            ValidationError::TableLength { fun, len } => {
+                write!(f, "{fun:?} has formulas for {len} basis elements")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks that the system only refers to defined variables, functions and basis elements,
+/// that functions given as tables have a formula for each basis element, and that functions
+/// are called with all the arguments their formulas use, which the solvers assume without
+/// checking. The formulas of all the functions are computed for each basis element.
+pub fn validate(
+    basis_count: usize,
+    eqs: &IndexedVec<VarId, FixEq>,
+    funs: &FunsFormulas,
+) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+
+    // The highest argument used by each function, with a basis element whose formula uses it.
+    let max_args = (0..funs.fun_count())
+        .map(FunId)
+        .map(|fun| {
+            if let Some(len) = funs.table_len(fun).filter(|&len| len != basis_count) {
+                errors.push(ValidationError::TableLength { fun, len });
+            }
+            let mut max_arg = None::<(VarId, BasisElemId)>;
+            for b in (0..basis_count).map(BasisElemId) {
+                for_each_atom(&funs.get(b, fun), &mut |elem, arg| {
+                    if elem.to_usize() >= basis_count {
+                        errors.push(ValidationError::BasisElemOutOfRange { fun, b, elem });
+                    }
+                    if max_arg.is_none_or(|(max, _)| arg > max) {
+                        max_arg = Some((arg, b));
+                    }
+                });
+            }
+            max_arg
+        })
+        .collect::<IndexedVec<FunId, _>>();
+
+    for (eq, fix_eq) in eqs.enumerate() {
+        validate_expr(&fix_eq.expr, eq, eqs.len(), &max_args, &mut errors);
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors),
+    }
+}
+
+fn validate_expr(
+    expr: &Expr,
+    eq: VarId,
+    var_count: usize,
+    max_args: &IndexedVec<FunId, Option<(VarId, BasisElemId)>>,
+    errors: &mut Vec<ValidationError>,
+) {
+    match expr {
+        Expr::Var(var) if var.to_usize() >= var_count => {
+            errors.push(ValidationError::UndefinedVar { eq, var: *var })
+        }
+        Expr::Var(_) => {}
+        Expr::And(children) | Expr::Or(children) => {
+            children.iter().for_each(|e| validate_expr(e, eq, var_count, max_args, errors))
+        }
+        Expr::Fun(fun, args) => {
+            match max_args.get(fun.to_usize()) {
+                None => errors.push(ValidationError::UndefinedFun { eq, fun: *fun }),
+                Some(&Some((arg, b))) if arg.to_usize() >= args.len() => {
+                    let args = args.len();
+                    errors.push(ValidationError::MissingArg { eq, fun: *fun, args, b, arg })
+                }
+                Some(_) => {}
+            }
+            args.iter().for_each(|e| validate_expr(e, eq, var_count, max_args, errors))
+        }
+    }
+}
+
+fn for_each_atom(formula: &Formula, f: &mut impl FnMut(BasisElemId, VarId)) {
+    match formula {
+        Formula::Atom(b, i) => f(*b, *i),
+        Formula::And(children) | Formula::Or(children) => {
+            children.iter().for_each(|child| for_each_atom(child, f))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, ValidationError};
+    use crate::index::IndexedVec;
+    use crate::symbolic::compose::FunsFormulas;
+    use crate::symbolic::eq::{Expr, FixEq, FixType, FunId, VarId};
+    use crate::symbolic::formula::{BasisElemId, Formula};
+
+    fn eqs(exprs: Vec<Expr>) -> IndexedVec<VarId, FixEq> {
+        exprs.into_iter().map(|expr| FixEq { fix_type: FixType::Min, expr }).collect()
+    }
+
+    #[test]
+    fn valid_system() {
+        // X0 = f(X1, X0), X1 = X0 || true
+        let eqs = eqs(vec![
+            Expr::Fun(FunId(0), vec![Expr::Var(VarId(1)), Expr::Var(VarId(0))]),
+            Expr::Or(vec![Expr::Var(VarId(0)), Expr::TOP]),
+        ]);
+        let f = vec![
+            Formula::Atom(BasisElemId(1), VarId(1)),
+            Formula::Or(vec![Formula::Atom(BasisElemId(0), VarId(0)), Formula::FALSE]),
+        ];
+        let funs = FunsFormulas::new(IndexedVec::from(vec![IndexedVec::from(f)]));
+
+        assert_eq!(validate(2, &eqs, &funs), Ok(()));
+    }
+
+    #[test]
+    fn invalid_system() {
+        // X0 = f(X2) && g(X0), X1 = f(X0, X1)
+        let eqs = eqs(vec![
+            Expr::And(vec![
+                Expr::Fun(FunId(0), vec![Expr::Var(VarId(2))]),
+                Expr::Fun(FunId(1), vec![Expr::Var(VarId(0))]),
+            ]),
+            Expr::Fun(FunId(0), vec![Expr::Var(VarId(0)), Expr::Var(VarId(1))]),
+        ]);
+        let f = vec![Formula::TRUE, Formula::Atom(BasisElemId(3), VarId(1))];
+        let funs = FunsFormulas::new(IndexedVec::from(vec![IndexedVec::from(f)]));
+
+        let (eq, fun, b) = (VarId(0), FunId(0), BasisElemId(1));
+        assert_eq!(
+            validate(2, &eqs, &funs),
+            Err(vec![
+                ValidationError::BasisElemOutOfRange { fun, b, elem: BasisElemId(3) },
+                ValidationError::MissingArg { eq, fun, args: 1, b, arg: VarId(1) },
+                ValidationError::UndefinedVar { eq, var: VarId(2) },
+                ValidationError::UndefinedFun { eq, fun: FunId(1) },
+            ])
+        );
+    }
+
+    #[test]
+    fn short_table() {
+        // X0 = f(X0), where f only has a formula for the first of 2 basis elements.
+        let eqs = eqs(vec![Expr::Fun(FunId(0), vec![Expr::Var(VarId(0))])]);
+        let f = vec![Formula::Atom(BasisElemId(0), VarId(0))];
+        let funs = FunsFormulas::new(IndexedVec::from(vec![IndexedVec::from(f)]));
+
+        assert_eq!(validate(1, &eqs, &funs), Ok(()));
+        assert_eq!(
+            validate(2, &eqs, &funs),
+            Err(vec![ValidationError::TableLength { fun: FunId(0), len: 1 }])
+        );
+    }
+}
@@ -0,0 +1,44 @@
+//! Types used to share data between queries. With the `sync` feature they are thread-safe,
+//! allowing to share the formulas and their caches between threads.
+
+#[cfg(not(feature = "sync"))]
+pub type Shared<T> = std::rc::Rc<T>;
+#[cfg(feature = "sync")]
+pub type Shared<T> = std::sync::Arc<T>;
+
+#[cfg(not(feature = "sync"))]
+pub type LockGuard<'a, T> = std::cell::RefMut<'a, T>;
+#[cfg(feature = "sync")]
+pub type LockGuard<'a, T> = std::sync::MutexGuard<'a, T>;
+
+/// Interior mutability, backed by a `RefCell` or by a `Mutex` with the `sync` feature.
+#[derive(Default)]
+pub struct Lock<T> {
+    #[cfg(not(feature = "sync"))]
+    inner: std::cell::RefCell<T>,
+    #[cfg(feature = "sync")]
+    inner: std::sync::Mutex<T>,
+}
+
+impl<T> Lock<T> {
+    pub fn new(value: T) -> Self {
+        Self { inner: value.into() }
+    }
+
+    #[cfg(not(feature = "sync"))]
+    pub fn lock(&self) -> LockGuard<'_, T> {
+        self.inner.borrow_mut()
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn lock(&self) -> LockGuard<'_, T> {
+        // A panic while holding the lock can't leave the caches in an inconsistent state.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T: Clone> Clone for Lock<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.lock().clone())
+    }
+}
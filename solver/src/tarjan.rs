@@ -0,0 +1,93 @@
+/// Iterative Tarjan's algorithm, reusing its buffers between calls.
+pub struct Tarjan<'a> {
+    succs: &'a [Vec<usize>],
+    alive: Vec<bool>,
+    index: Vec<usize>,
+    low: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+}
+
+impl<'a> Tarjan<'a> {
+    const UNVISITED: usize = usize::MAX;
+
+    pub fn new(succs: &'a [Vec<usize>]) -> Self {
+        let n = succs.len();
+        Self {
+            succs,
+            alive: vec![false; n],
+            index: vec![Self::UNVISITED; n],
+            low: vec![0; n],
+            on_stack: vec![false; n],
+            stack: Vec::new(),
+        }
+    }
+
+    /// Computes the SCCs of the subgraph induced by `nodes`.
+    pub fn sccs(&mut self, nodes: &[usize]) -> Vec<Vec<usize>> {
+        nodes.iter().for_each(|&n| self.alive[n] = true);
+
+        let mut next_index = 0;
+        let mut out = Vec::new();
+
+        for &root in nodes {
+            if self.index[root] != Self::UNVISITED {
+                continue;
+            }
+
+            // Nodes being visited, with the position of the next successor to visit.
+            let mut call_stack = vec![(root, 0)];
+            self.visit(root, &mut next_index);
+
+            while let Some(&mut (n, ref mut pos)) = call_stack.last_mut() {
+                if let Some(&m) = self.succs[n].get(*pos) {
+                    *pos += 1;
+                    if !self.alive[m] {
+                        continue;
+                    }
+                    if self.index[m] == Self::UNVISITED {
+                        self.visit(m, &mut next_index);
+                        call_stack.push((m, 0));
+                    } else if self.on_stack[m] {
+                        self.low[n] = self.low[n].min(self.index[m]);
+                    }
+                    continue;
+                }
+
+                call_stack.pop();
+                if let Some(&(parent, _)) = call_stack.last() {
+                    self.low[parent] = self.low[parent].min(self.low[n]);
+                }
+
+                if self.low[n] == self.index[n] {
+                    let mut scc = Vec::new();
+                    loop {
+                        let m = self.stack.pop().unwrap();
+                        self.on_stack[m] = false;
+                        scc.push(m);
+                        if m == n {
+                            break;
+                        }
+                    }
+                    out.push(scc);
+                }
+            }
+        }
+
+        // Reset the state of the nodes, so that the next call can use a different subgraph.
+        for &n in nodes {
+            self.alive[n] = false;
+            self.index[n] = Self::UNVISITED;
+        }
+
+        out
+    }
+
+    fn visit(&mut self, n: usize, next_index: &mut usize) {
+        self.index[n] = *next_index;
+        self.low[n] = *next_index;
+        *next_index += 1;
+        self.stack.push(n);
+        self.on_stack[n] = true;
+    }
+}